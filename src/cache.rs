@@ -0,0 +1,69 @@
+//! Small, fixed-capacity LRU cache used to bound the memory used for
+//! cached certificates, chains and AIKs on a device with tiny storage.
+
+use heapless::Vec;
+
+pub struct LruCache<K, V, const N: usize> {
+    // Front (index 0) is most-recently-used.
+    entries: Vec<(K, V), N>,
+}
+
+impl<K: PartialEq + Clone, V, const N: usize> LruCache<K, V, N> {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos);
+        self.entries.insert(0, entry).ok();
+        self.entries.first().map(|(_, v)| v)
+    }
+
+    /// Insert `key`/`value`, evicting the least-recently-used entry if the
+    /// cache is already at capacity.
+    pub fn put(&mut self, key: K, value: V) {
+        if let Some(pos) = self.entries.iter().position(|(k, _)| k == &key) {
+            self.entries.remove(pos);
+        } else if self.entries.len() == N {
+            self.entries.pop();
+        }
+        // `insert` only fails if already at capacity, which the eviction
+        // above rules out.
+        let _ = self.entries.insert(0, (key, value));
+    }
+}
+
+impl<K: PartialEq + Clone, V, const N: usize> Default for LruCache<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserting_beyond_capacity_evicts_least_recently_used() {
+        let mut cache: LruCache<u32, &'static str, 2> = LruCache::new();
+        cache.put(1, "a");
+        cache.put(2, "b");
+        // Touch 1 so it's most-recently-used, leaving 2 as the LRU entry.
+        cache.get(&1);
+        cache.put(3, "c");
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&1).is_some());
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&3).is_some());
+    }
+}
@@ -0,0 +1,59 @@
+//! Single source of truth for which algorithms this build supports.
+//!
+//! Queried through [`Capabilities::supports_hash`] and
+//! [`Capabilities::supports_signature_scheme`]. No handshake in this tree
+//! advertises it, and no verification path consults it when checking
+//! what an attester sends.
+
+use crate::tpm::Algorithm;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureScheme {
+    RsaSsaPkcs1Sha256,
+    EcdsaP256Sha256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    Rsa2048,
+    EccP256,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub hashes: &'static [Algorithm],
+    pub signature_schemes: &'static [SignatureScheme],
+    pub key_types: &'static [KeyType],
+}
+
+/// Mechanisms this build actually implements. Anything not listed here
+/// must be rejected by the verification path, not just left unadvertised.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        hashes: &[Algorithm::Sha256],
+        signature_schemes: &[SignatureScheme::RsaSsaPkcs1Sha256, SignatureScheme::EcdsaP256Sha256],
+        key_types: &[KeyType::Rsa2048, KeyType::EccP256],
+    }
+}
+
+impl Capabilities {
+    pub fn supports_hash(&self, alg: Algorithm) -> bool {
+        self.hashes.contains(&alg)
+    }
+
+    pub fn supports_signature_scheme(&self, scheme: SignatureScheme) -> bool {
+        self.signature_schemes.contains(&scheme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unadvertised_hash_is_reported_unsupported() {
+        let caps = capabilities();
+        assert!(!caps.supports_hash(Algorithm::Sha1));
+        assert!(caps.supports_hash(Algorithm::Sha256));
+    }
+}
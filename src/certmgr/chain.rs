@@ -0,0 +1,292 @@
+//! EK certificate chain verification.
+//!
+//! A TPM EK certificate typically chains through one or more manufacturer
+//! intermediate CAs up to a trust anchor pinned on the device (see
+//! [`super::store`]). Signature verification itself is abstracted behind
+//! [`ChainVerifier`] — the same pattern [`super::manufacturer`] and
+//! [`super::encrypted_store`] use for `verify_signature`/`Aead` — so the
+//! chain-ordering and `basicConstraints` logic here can be exercised
+//! without a real X.509 parser, which does not exist in this tree yet.
+
+/// A certificate reduced to the fields chain verification needs: its
+/// subject/issuer names (opaque byte strings, e.g. DER-encoded Name), the
+/// `basicConstraints` CA flag, the `keyUsage` `keyCertSign` bit, and the
+/// to-be-signed body plus signature needed to check who signed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Certificate<'a> {
+    pub subject: &'a [u8],
+    pub issuer: &'a [u8],
+    pub is_ca: bool,
+    /// Whether the `keyUsage` extension has the `keyCertSign` bit set.
+    /// Required on every non-leaf certificate in addition to
+    /// `basicConstraints` CA=true: a CA cert can be marked CA=true but
+    /// still be scoped by `keyUsage` to something other than issuing
+    /// further certificates.
+    pub key_cert_sign: bool,
+    pub tbs: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+/// Verifies that `signature` over `tbs` was produced by the key belonging
+/// to `issuer_subject`. Kept as a trait so chain-ordering logic can be
+/// tested independently of a real RSA/ECDSA signature check.
+pub trait ChainVerifier {
+    fn verify_signed_by(&self, tbs: &[u8], signature: &[u8], issuer_subject: &[u8]) -> bool;
+}
+
+/// Chain depth this device is willing to walk before giving up; TPM EK
+/// chains are never more than a couple of levels deep in practice.
+const MAX_CHAIN_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    /// The chain is longer than `MAX_CHAIN_DEPTH`.
+    TooLong,
+    /// No intermediate (or the trust anchor) matches the next issuer in
+    /// the chain.
+    BrokenLink,
+    /// An intermediate required to sign the next certificate down does
+    /// not have `basicConstraints` CA=true.
+    NotCa,
+    /// An intermediate required to sign the next certificate down does
+    /// not have the `keyUsage` `keyCertSign` bit set.
+    MissingKeyCertSign,
+    /// A signature in the chain doesn't verify.
+    InvalidSignature,
+    /// The chain never reaches `trust_anchor_subject`.
+    UntrustedRoot,
+    /// The leaf certificate is self-signed (issuer equals its own
+    /// subject) but isn't itself the pinned trust anchor.
+    UntrustedSelfSigned,
+}
+
+/// Order `intermediates` (which may arrive in any order from the
+/// attester) into a validated chain from `leaf` up to
+/// `trust_anchor_subject`, checking each signature link and enforcing
+/// `basicConstraints` CA=true and `keyUsage` `keyCertSign` on every
+/// non-leaf certificate.
+///
+/// Returns the validated chain, leaf first, on success.
+pub fn verify_chain<'a, V: ChainVerifier>(
+    verifier: &V,
+    leaf: &Certificate<'a>,
+    intermediates: &[Certificate<'a>],
+    trust_anchor_subject: &[u8],
+) -> Result<heapless::Vec<Certificate<'a>, MAX_CHAIN_DEPTH>, ChainError> {
+    if intermediates.len() >= MAX_CHAIN_DEPTH {
+        return Err(ChainError::TooLong);
+    }
+
+    // A self-signed leaf (its own issuer) is only trustworthy if it *is*
+    // the pinned trust anchor itself; anything else self-signed is
+    // exactly what an attacker presenting a forged, unpinned root would
+    // hand us, and deserves a distinct error from a chain that simply
+    // never reaches a trust anchor.
+    if leaf.issuer == leaf.subject && leaf.subject != trust_anchor_subject {
+        return Err(ChainError::UntrustedSelfSigned);
+    }
+
+    let mut chain = heapless::Vec::new();
+    chain.push(*leaf).ok();
+
+    let mut used = [false; MAX_CHAIN_DEPTH];
+    let mut current = *leaf;
+
+    loop {
+        if verifier.verify_signed_by(current.tbs, current.signature, trust_anchor_subject) && current.issuer == trust_anchor_subject {
+            return Ok(chain);
+        }
+
+        let next_idx = intermediates
+            .iter()
+            .enumerate()
+            .find(|(i, cert)| !used[*i] && cert.subject == current.issuer);
+
+        let (idx, next) = match next_idx {
+            Some((i, cert)) => (i, cert),
+            None => return Err(ChainError::UntrustedRoot),
+        };
+
+        if !next.is_ca {
+            return Err(ChainError::NotCa);
+        }
+
+        if !next.key_cert_sign {
+            return Err(ChainError::MissingKeyCertSign);
+        }
+
+        if !verifier.verify_signed_by(current.tbs, current.signature, next.subject) {
+            return Err(ChainError::InvalidSignature);
+        }
+
+        used[idx] = true;
+        chain.push(*next).ok();
+        current = *next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trusts a signature iff it was recorded as valid for that exact
+    /// (tbs, signature, issuer_subject) triple.
+    struct FakeVerifier {
+        valid: heapless::Vec<(&'static [u8], &'static [u8], &'static [u8]), 8>,
+    }
+
+    impl ChainVerifier for FakeVerifier {
+        fn verify_signed_by(&self, tbs: &[u8], signature: &[u8], issuer_subject: &[u8]) -> bool {
+            self.valid.iter().any(|(t, s, i)| *t == tbs && *s == signature && *i == issuer_subject)
+        }
+    }
+
+    #[test]
+    fn verifies_a_two_level_chain() {
+        let root_subject = b"root-ca".as_slice();
+        let intermediate_subject = b"intermediate-ca".as_slice();
+        let leaf_subject = b"attester-ek".as_slice();
+
+        let leaf = Certificate {
+            subject: leaf_subject,
+            issuer: intermediate_subject,
+            is_ca: false,
+            key_cert_sign: true,
+            tbs: b"leaf-tbs",
+            signature: b"leaf-sig",
+        };
+        let intermediate = Certificate {
+            subject: intermediate_subject,
+            issuer: root_subject,
+            is_ca: true,
+            key_cert_sign: true,
+            tbs: b"intermediate-tbs",
+            signature: b"intermediate-sig",
+        };
+
+        let mut valid = heapless::Vec::new();
+        valid.push((b"leaf-tbs".as_slice(), b"leaf-sig".as_slice(), intermediate_subject)).ok();
+        valid.push((b"intermediate-tbs".as_slice(), b"intermediate-sig".as_slice(), root_subject)).ok();
+        let verifier = FakeVerifier { valid };
+
+        let chain = verify_chain(&verifier, &leaf, &[intermediate], root_subject).unwrap();
+
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].subject, leaf_subject);
+        assert_eq!(chain[1].subject, intermediate_subject);
+    }
+
+    #[test]
+    fn rejects_an_intermediate_missing_the_ca_flag() {
+        let root_subject = b"root-ca".as_slice();
+        let intermediate_subject = b"intermediate-ca".as_slice();
+
+        let leaf = Certificate {
+            subject: b"attester-ek",
+            issuer: intermediate_subject,
+            is_ca: false,
+            key_cert_sign: true,
+            tbs: b"leaf-tbs",
+            signature: b"leaf-sig",
+        };
+        let not_a_ca = Certificate {
+            subject: intermediate_subject,
+            issuer: root_subject,
+            is_ca: false,
+            key_cert_sign: true,
+            tbs: b"intermediate-tbs",
+            signature: b"intermediate-sig",
+        };
+
+        let mut valid = heapless::Vec::new();
+        valid.push((b"leaf-tbs".as_slice(), b"leaf-sig".as_slice(), intermediate_subject)).ok();
+        let verifier = FakeVerifier { valid };
+
+        assert_eq!(verify_chain(&verifier, &leaf, &[not_a_ca], root_subject), Err(ChainError::NotCa));
+    }
+
+    #[test]
+    fn rejects_a_chain_that_never_reaches_the_trust_anchor() {
+        let leaf = Certificate {
+            subject: b"attester-ek",
+            issuer: b"unknown-issuer",
+            is_ca: false,
+            key_cert_sign: true,
+            tbs: b"leaf-tbs",
+            signature: b"leaf-sig",
+        };
+        let verifier = FakeVerifier { valid: heapless::Vec::new() };
+
+        assert_eq!(verify_chain(&verifier, &leaf, &[], b"root-ca"), Err(ChainError::UntrustedRoot));
+    }
+
+    #[test]
+    fn a_self_signed_leaf_pinned_as_the_trust_anchor_is_accepted() {
+        let subject = b"device-root".as_slice();
+        let leaf = Certificate {
+            subject,
+            issuer: subject,
+            is_ca: true,
+            key_cert_sign: true,
+            tbs: b"root-tbs",
+            signature: b"root-sig",
+        };
+
+        let mut valid = heapless::Vec::new();
+        valid.push((b"root-tbs".as_slice(), b"root-sig".as_slice(), subject)).ok();
+        let verifier = FakeVerifier { valid };
+
+        let chain = verify_chain(&verifier, &leaf, &[], subject).unwrap();
+        assert_eq!(chain.len(), 1);
+    }
+
+    #[test]
+    fn a_self_signed_leaf_that_is_not_the_pinned_trust_anchor_is_rejected() {
+        let leaf = Certificate {
+            subject: b"forged-root",
+            issuer: b"forged-root",
+            is_ca: true,
+            key_cert_sign: true,
+            tbs: b"forged-tbs",
+            signature: b"forged-sig",
+        };
+        let verifier = FakeVerifier { valid: heapless::Vec::new() };
+
+        assert_eq!(
+            verify_chain(&verifier, &leaf, &[], b"device-root"),
+            Err(ChainError::UntrustedSelfSigned)
+        );
+    }
+
+    #[test]
+    fn rejects_an_intermediate_missing_the_key_cert_sign_bit() {
+        let root_subject = b"root-ca".as_slice();
+        let intermediate_subject = b"intermediate-ca".as_slice();
+
+        let leaf = Certificate {
+            subject: b"attester-ek",
+            issuer: intermediate_subject,
+            is_ca: false,
+            key_cert_sign: true,
+            tbs: b"leaf-tbs",
+            signature: b"leaf-sig",
+        };
+        let scoped_out = Certificate {
+            subject: intermediate_subject,
+            issuer: root_subject,
+            is_ca: true,
+            key_cert_sign: false,
+            tbs: b"intermediate-tbs",
+            signature: b"intermediate-sig",
+        };
+
+        let mut valid = heapless::Vec::new();
+        valid.push((b"leaf-tbs".as_slice(), b"leaf-sig".as_slice(), intermediate_subject)).ok();
+        let verifier = FakeVerifier { valid };
+
+        assert_eq!(
+            verify_chain(&verifier, &leaf, &[scoped_out], root_subject),
+            Err(ChainError::MissingKeyCertSign)
+        );
+    }
+}
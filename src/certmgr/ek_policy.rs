@@ -0,0 +1,121 @@
+//! Pluggable trust policy applied to an EK certificate after its
+//! signature chain has already verified against a pinned trust anchor
+//! (see [`super::chain`]).
+//!
+//! There's no real `X509Certificate` type anywhere in this tree — EK
+//! certs are reduced to [`super::chain::Certificate`], the same
+//! abstraction chain verification itself uses — so the hook here checks
+//! that type rather than a parsed X.509 structure.
+
+use super::chain::Certificate;
+use super::fingerprint::{fingerprint, Fingerprint};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    /// The certificate's fingerprint isn't on the configured allowlist.
+    NotAllowlisted,
+}
+
+/// A trust-policy check run on an EK certificate once chain verification
+/// has already succeeded. Chain verification proves the certificate was
+/// issued by a CA the device trusts; this is an additional gate a caller
+/// can plug in to further restrict which of those otherwise-valid
+/// certificates are actually accepted.
+///
+/// `FobnailClient::handle_ek_cert_response` is the real caller: it runs
+/// this after `CertMgr::verify_ek_chain`'s chain walk succeeds, and only
+/// advances to `State::VerifyEkCert` if the policy also accepts the leaf.
+pub trait EkPolicy {
+    fn check(&self, cert: &Certificate) -> Result<(), PolicyError>;
+}
+
+/// Accepts every certificate that passed chain verification. The default
+/// policy: trusting the CA hierarchy alone is sufficient unless a device
+/// operator opts into something stricter.
+pub struct AcceptAll;
+
+impl EkPolicy for AcceptAll {
+    fn check(&self, _cert: &Certificate) -> Result<(), PolicyError> {
+        Ok(())
+    }
+}
+
+/// Restricts accepted EK certificates to a fixed set of fingerprints
+/// (see [`super::fingerprint`]) pinned ahead of time, e.g. during device
+/// provisioning against a known manufacturer's issued serial range.
+pub struct ManufacturerAllowlist<const N: usize> {
+    allowed: heapless::Vec<Fingerprint, N>,
+}
+
+impl<const N: usize> ManufacturerAllowlist<N> {
+    pub fn new() -> Self {
+        Self { allowed: heapless::Vec::new() }
+    }
+
+    /// Add a fingerprint to the allowlist. Fails if the allowlist is
+    /// already at capacity `N`.
+    pub fn allow(&mut self, fp: Fingerprint) -> Result<(), Fingerprint> {
+        self.allowed.push(fp)
+    }
+}
+
+impl<const N: usize> Default for ManufacturerAllowlist<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> EkPolicy for ManufacturerAllowlist<N> {
+    fn check(&self, cert: &Certificate) -> Result<(), PolicyError> {
+        if self.allowed.contains(&fingerprint(cert.tbs)) {
+            Ok(())
+        } else {
+            Err(PolicyError::NotAllowlisted)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cert(tbs: &'static [u8]) -> Certificate<'static> {
+        Certificate {
+            subject: b"attester-ek",
+            issuer: b"intermediate-ca",
+            is_ca: false,
+            key_cert_sign: false,
+            tbs,
+            signature: b"sig",
+        }
+    }
+
+    #[test]
+    fn accept_all_accepts_anything() {
+        assert_eq!(AcceptAll.check(&cert(b"leaf-tbs")), Ok(()));
+    }
+
+    #[test]
+    fn allowlist_accepts_a_pinned_fingerprint() {
+        let mut policy: ManufacturerAllowlist<4> = ManufacturerAllowlist::new();
+        policy.allow(fingerprint(b"leaf-tbs")).unwrap();
+
+        assert_eq!(policy.check(&cert(b"leaf-tbs")), Ok(()));
+    }
+
+    #[test]
+    fn allowlist_rejects_an_unpinned_fingerprint() {
+        let mut policy: ManufacturerAllowlist<4> = ManufacturerAllowlist::new();
+        policy.allow(fingerprint(b"some-other-cert")).unwrap();
+
+        assert_eq!(policy.check(&cert(b"leaf-tbs")), Err(PolicyError::NotAllowlisted));
+    }
+
+    #[test]
+    fn a_full_allowlist_refuses_further_pins() {
+        let mut policy: ManufacturerAllowlist<1> = ManufacturerAllowlist::new();
+        policy.allow(fingerprint(b"first")).unwrap();
+
+        assert_eq!(policy.allow(fingerprint(b"second")), Err(fingerprint(b"second")));
+    }
+}
@@ -0,0 +1,117 @@
+//! Optional at-rest encryption for stored `/meta` records.
+//!
+//! Plaintext records let an attacker who extracts the flash read
+//! provisioned attester identities. When enabled, records are encrypted
+//! with AES-GCM under a device key held in Trussed before being written,
+//! and decrypted (with tag verification) on read, making them
+//! confidential and tamper-evident. Off by default to avoid the extra
+//! Trussed round-trip on devices that don't need it.
+
+/// AES-GCM operations backed by a Trussed-held device key. A real backend
+/// calls into `trussed.encrypt`/`trussed.decrypt` with a key handle that
+/// never leaves Trussed; kept as a trait so storage logic can be tested
+/// without one.
+pub trait Aead {
+    fn seal(&self, plaintext: &[u8], nonce: &[u8; 12]) -> heapless::Vec<u8, 576>;
+    fn open(&self, ciphertext: &[u8], nonce: &[u8; 12]) -> Result<heapless::Vec<u8, 512>, AeadError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AeadError {
+    AuthenticationFailed,
+}
+
+pub struct EncryptedMetadataStore<A> {
+    aead: A,
+    enabled: bool,
+}
+
+impl<A: Aead> EncryptedMetadataStore<A> {
+    /// Encryption is opt-in: `enabled` must be explicitly set by
+    /// configuration.
+    pub fn new(aead: A, enabled: bool) -> Self {
+        Self { aead, enabled }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the bytes to write to flash: ciphertext when encryption is
+    /// enabled, the plaintext unchanged otherwise.
+    pub fn prepare_for_write(&self, record: &[u8], nonce: &[u8; 12]) -> heapless::Vec<u8, 576> {
+        if self.enabled {
+            self.aead.seal(record, nonce)
+        } else {
+            let mut v = heapless::Vec::new();
+            let _ = v.extend_from_slice(record);
+            v
+        }
+    }
+
+    /// Recover the plaintext record from what was read off flash.
+    pub fn read(&self, stored: &[u8], nonce: &[u8; 12]) -> Result<heapless::Vec<u8, 512>, AeadError> {
+        if self.enabled {
+            self.aead.open(stored, nonce)
+        } else {
+            let mut v = heapless::Vec::new();
+            let _ = v.extend_from_slice(stored);
+            Ok(v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// XOR-with-key-and-append-tag stand-in for AES-GCM, sufficient to
+    /// exercise the storage logic without a real cipher.
+    struct FakeAead {
+        key: u8,
+    }
+
+    impl Aead for FakeAead {
+        fn seal(&self, plaintext: &[u8], _nonce: &[u8; 12]) -> heapless::Vec<u8, 576> {
+            let mut out = heapless::Vec::new();
+            for b in plaintext {
+                let _ = out.push(b ^ self.key);
+            }
+            let _ = out.push(self.key); // stand-in auth tag
+            out
+        }
+
+        fn open(&self, ciphertext: &[u8], _nonce: &[u8; 12]) -> Result<heapless::Vec<u8, 512>, AeadError> {
+            let (body, tag) = ciphertext.split_at(ciphertext.len() - 1);
+            if tag != [self.key] {
+                return Err(AeadError::AuthenticationFailed);
+            }
+            let mut out = heapless::Vec::new();
+            for b in body {
+                let _ = out.push(b ^ self.key);
+            }
+            Ok(out)
+        }
+    }
+
+    #[test]
+    fn disabled_stores_plaintext_unchanged() {
+        let store = EncryptedMetadataStore::new(FakeAead { key: 0x42 }, false);
+        let record = b"attester-42-metadata";
+        let stored = store.prepare_for_write(record, &[0u8; 12]);
+        assert_eq!(stored.as_slice(), record);
+    }
+
+    #[test]
+    fn enabled_round_trips_through_encryption() {
+        let store = EncryptedMetadataStore::new(FakeAead { key: 0x42 }, true);
+        let record = b"attester-42-metadata";
+        let nonce = [1u8; 12];
+
+        let stored = store.prepare_for_write(record, &nonce);
+        assert_ne!(stored.as_slice(), record.as_slice());
+
+        let recovered = store.read(&stored, &nonce).unwrap();
+        assert_eq!(recovered.as_slice(), record.as_slice());
+    }
+}
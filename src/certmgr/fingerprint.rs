@@ -0,0 +1,95 @@
+//! Certificate fingerprinting, shared by EK pinning and the artifact
+//! cache.
+//!
+//! Certs can be large enough that copying them whole into a fixed-size
+//! buffer before hashing risks a capacity panic. Hashing incrementally as
+//! the cert is parsed or reassembled avoids ever needing the whole thing
+//! contiguous at once.
+
+use crate::crypto::Sha256;
+
+pub type Fingerprint = [u8; 32];
+
+/// Incrementally computed fingerprint; feed it chunks as they arrive (or
+/// as they're consumed while parsing) and call [`Fingerprinter::finish`]
+/// once the whole certificate has been seen.
+#[derive(Default)]
+pub struct Fingerprinter(Sha256);
+
+impl Fingerprinter {
+    pub fn new() -> Self {
+        Self(Sha256::new())
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.0.update(chunk);
+    }
+
+    pub fn finish(self) -> Fingerprint {
+        self.0.finalize()
+    }
+}
+
+/// Convenience wrapper for a certificate that's already fully assembled
+/// in memory.
+pub fn fingerprint(cert_der: &[u8]) -> Fingerprint {
+    let mut fp = Fingerprinter::new();
+    fp.feed(cert_der);
+    fp.finish()
+}
+
+/// Lowercase hex digits, indexed by nibble.
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Format `hash` as lowercase hex, e.g. for use as a
+/// [`crate::certmgr::CertMgr::store_metadata_hash`] name. Writes into a
+/// stack-allocated `heapless::String` rather than `alloc::format!`, so
+/// hashing a lot of attesters' EK fingerprints doesn't churn the
+/// allocator on the heap-constrained target.
+///
+/// Capacity is 64 hex characters (32 hash bytes); longer inputs are
+/// truncated to what fits rather than panicking, since a caller passing
+/// something bigger than a SHA-256 digest here is already a bug.
+pub fn format_hash(hash: &[u8]) -> heapless::String<64> {
+    let mut out = heapless::String::new();
+    for &byte in hash.iter().take(32) {
+        // `heapless::String::push` can't fail here: each byte contributes
+        // exactly 2 of the 64 characters the buffer is sized for.
+        let _ = out.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        let _ = out.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streamed_fingerprint_matches_one_shot_for_small_cert() {
+        let cert = b"pretend-this-is-a-DER-encoded-certificate";
+
+        let one_shot = fingerprint(cert);
+
+        let mut streamed = Fingerprinter::new();
+        streamed.feed(&cert[..10]);
+        streamed.feed(&cert[10..]);
+
+        assert_eq!(one_shot, streamed.finish());
+    }
+
+    #[test]
+    fn format_hash_matches_heap_formatted_lowercase_hex() {
+        let hash = fingerprint(b"pretend-this-is-a-DER-encoded-certificate");
+
+        let expected: alloc::string::String =
+            hash.iter().map(|b| alloc::format!("{:02x}", b)).collect();
+
+        assert_eq!(format_hash(&hash).as_str(), expected.as_str());
+    }
+
+    #[test]
+    fn format_hash_is_exactly_two_hex_chars_per_byte() {
+        assert_eq!(format_hash(&[0xde, 0xad, 0xbe, 0xef]).as_str(), "deadbeef");
+    }
+}
@@ -0,0 +1,29 @@
+//! Public-key material extracted from a certificate's
+//! SubjectPublicKeyInfo.
+//!
+//! No X.509 decoder exists in this tree yet ([`super::chain`] treats
+//! certificates as opaque signed blobs pending one), so this only defines
+//! the key shapes such a decoder will need to produce. `Ec` covers the
+//! NIST curves TPM EKs commonly use; `Rsa` covers the modulus/exponent
+//! pair `certmgr::chain::ChainVerifier` implementations need for RSA
+//! signature checks.
+//!
+//! The ECC EK path itself (`MakeCredential` via ephemeral ECDH) is
+//! already handled independently of this type — see
+//! `crate::tpm::mc::EkKey::Ecc` and `crate::tpm::mc_ecc` — so an `Ec` key
+//! here does not need special-casing downstream once a decoder exists to
+//! produce one.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcCurve {
+    P256,
+    P384,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Key {
+    /// Boxed so a `Key::Ec` (much smaller) doesn't pay for the RSA
+    /// variant's 512-byte modulus buffer in every `Key` on the stack.
+    Rsa { n: alloc::boxed::Box<heapless::Vec<u8, 512>>, e: u32 },
+    Ec { curve: EcCurve, x: heapless::Vec<u8, 48>, y: heapless::Vec<u8, 48> },
+}
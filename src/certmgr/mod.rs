@@ -0,0 +1,26 @@
+//! Certificate and metadata-hash storage on top of the Trussed filesystem.
+
+mod chain;
+mod ek_policy;
+mod encrypted_store;
+mod fingerprint;
+mod key;
+mod manufacturer;
+mod size_limit;
+mod store;
+mod validity;
+
+pub use chain::{verify_chain, ChainError, ChainVerifier, Certificate};
+pub use ek_policy::{AcceptAll, EkPolicy, ManufacturerAllowlist, PolicyError};
+pub use encrypted_store::{Aead, AeadError, EncryptedMetadataStore};
+pub use fingerprint::{fingerprint, format_hash, Fingerprint, Fingerprinter};
+pub use key::{EcCurve, Key};
+pub use manufacturer::{verify_manufacturer_attestation, ManufacturerAttestation, ManufacturerAttestationError};
+pub use size_limit::{
+    check_size, SizeLimitError, MAX_AIK_PAYLOAD_LEN, MAX_EK_CERT_DER_LEN, MAX_METADATA_PAYLOAD_LEN,
+};
+pub use store::{
+    is_valid_hex, CertMgr, EkVerifyError, Filesystem, InitOutcome, LocateResult, MetadataDeleteError, StorageError,
+    StorageStats, TrustAnchor, METADATA_HASH_CAPACITY, TRUST_ANCHOR_DIR,
+};
+pub use validity::{check_validity, ValidityError, ValidityWindow};
@@ -0,0 +1,74 @@
+//! Size guards applied to attester-supplied binary blobs before they're
+//! parsed, so an oversized EK certificate, AIK public area, or metadata
+//! payload is rejected outright instead of being handed to a parser
+//! sized for something much smaller.
+//!
+//! There's no `load_cert_owned` (or any concrete X.509 parser) anywhere
+//! in this tree yet — see [`super::chain`] for the same caveat about EK
+//! certificates generally — so this is the bounds check such a call
+//! would apply first, kept standalone so it's covered regardless of when
+//! the parser itself lands.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeLimitError {
+    /// `len` exceeds the `max` this category of payload is allowed.
+    TooLarge { len: usize, max: usize },
+}
+
+/// Maximum size accepted for an EK certificate's DER encoding. TPM EK
+/// certs are ordinarily well under 2 KiB; 8 KiB leaves generous headroom
+/// for an unusually large certificate without letting an attester force
+/// an arbitrarily large parse.
+pub const MAX_EK_CERT_DER_LEN: usize = 8 * 1024;
+
+/// Maximum size accepted for an AIK public-area payload. AIK public areas
+/// are small, fixed-shape structures (see
+/// [`crate::tpm::aik::AikPublicKey`]); this is generous headroom above
+/// even the largest (8192-bit RSA) modulus this device will trust.
+pub const MAX_AIK_PAYLOAD_LEN: usize = 2 * 1024;
+
+/// Maximum size accepted for a metadata payload (see
+/// [`crate::proto::Metadata`]) before it's decoded.
+pub const MAX_METADATA_PAYLOAD_LEN: usize = 4 * 1024;
+
+/// Reject `bytes` outright if it's longer than `max`, before it's handed
+/// to any parser.
+pub fn check_size(bytes: &[u8], max: usize) -> Result<(), SizeLimitError> {
+    if bytes.len() > max {
+        Err(SizeLimitError::TooLarge { len: bytes.len(), max })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_payload_within_the_limit_is_accepted() {
+        let der = [0u8; MAX_EK_CERT_DER_LEN];
+        assert_eq!(check_size(&der, MAX_EK_CERT_DER_LEN), Ok(()));
+    }
+
+    #[test]
+    fn an_oversized_ek_cert_der_is_rejected() {
+        let der = [0u8; MAX_EK_CERT_DER_LEN + 1];
+        assert_eq!(
+            check_size(&der, MAX_EK_CERT_DER_LEN),
+            Err(SizeLimitError::TooLarge { len: MAX_EK_CERT_DER_LEN + 1, max: MAX_EK_CERT_DER_LEN })
+        );
+    }
+
+    #[test]
+    fn an_oversized_aik_payload_is_rejected() {
+        let payload = [0u8; MAX_AIK_PAYLOAD_LEN + 1];
+        assert!(check_size(&payload, MAX_AIK_PAYLOAD_LEN).is_err());
+    }
+
+    #[test]
+    fn an_oversized_metadata_payload_is_rejected() {
+        let payload = [0u8; MAX_METADATA_PAYLOAD_LEN + 1];
+        assert!(check_size(&payload, MAX_METADATA_PAYLOAD_LEN).is_err());
+    }
+}
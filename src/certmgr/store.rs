@@ -0,0 +1,628 @@
+//! Metadata-hash lookups against the Trussed filesystem.
+
+use super::chain::{verify_chain, ChainError, ChainVerifier, Certificate};
+use super::ek_policy::{EkPolicy, PolicyError};
+use crate::certmgr::fingerprint;
+use crate::certmgr::Fingerprint;
+
+/// Trussed filesystem operations `CertMgr` depends on. Kept as a trait so
+/// tests can simulate a filesystem in an inconsistent state (e.g.
+/// corruption) without a real Trussed backend.
+pub trait Filesystem {
+    /// Resolve a logical name to a path, if the filesystem believes an
+    /// entry exists. This is a directory-only lookup: it does not
+    /// guarantee the file is actually readable.
+    fn locate_file(&self, name: &str) -> Option<heapless::String<64>>;
+
+    /// Attempt to open a path for reading, returning an error if it can't
+    /// actually be read (e.g. dangling directory entry after corruption).
+    fn open(&self, path: &str) -> Result<(), StorageError>;
+
+    /// Whether the filesystem has been formatted (a fresh device, or one
+    /// recovering from corruption, reports `false`).
+    fn is_formatted(&self) -> bool;
+
+    /// Format the filesystem, destroying any data present.
+    fn format(&mut self);
+
+    /// List file paths directly under `prefix` (e.g. `/trust/`).
+    fn list_files(&self, prefix: &str) -> heapless::Vec<heapless::String<64>, 8>;
+
+    /// Read a whole file's contents.
+    fn read_file(&self, path: &str) -> Result<heapless::Vec<u8, 1024>, StorageError>;
+
+    /// Write `data` to `path`, creating or overwriting it.
+    fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), StorageError>;
+
+    /// Delete `path`. Deleting a path that doesn't exist is not an error.
+    fn delete_file(&mut self, path: &str) -> Result<(), StorageError>;
+}
+
+/// Directory trust anchors are read from and written to (see
+/// [`CertMgr::load_trust_anchors`]).
+pub const TRUST_ANCHOR_DIR: &str = "/trust/";
+
+/// Directory metadata-hash marker files are written to (see
+/// [`CertMgr::store_metadata_hash`]).
+pub const METADATA_HASH_DIR: &str = "/meta/";
+
+/// A DER-encoded root CA certificate pinned as a trust anchor, along with
+/// its fingerprint for quick comparison against a chain's claimed root.
+pub struct TrustAnchor {
+    pub der: heapless::Vec<u8, 1024>,
+    pub fingerprint: Fingerprint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    NotFound,
+    Corrupted,
+    /// `/meta/` already holds [`METADATA_HASH_CAPACITY`] entries; nothing
+    /// was written.
+    Full,
+}
+
+/// How many metadata-hash marker files `/meta/` can hold, matching
+/// `list_metadata_hashes`'s fixed-capacity return buffer. There's no real
+/// littlefs/NVMC wear-leveling layer in this tree to size this against, so
+/// this stands in for "the store is full" the way a real backend would
+/// eventually report `ENOSPC`.
+pub const METADATA_HASH_CAPACITY: usize = 8;
+
+/// A snapshot of how much of the metadata-hash store is in use, so a
+/// caller can warn or refuse new attesters before a write actually fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageStats {
+    pub used: usize,
+    pub capacity: usize,
+}
+
+impl StorageStats {
+    pub fn is_full(&self) -> bool {
+        self.used >= self.capacity
+    }
+}
+
+/// Why [`CertMgr::remove_metadata_hash_checked`] refused to delete an
+/// entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataDeleteError {
+    /// The requested name isn't a well-formed hex string, so it can't be
+    /// a metadata hash this store would ever have written.
+    InvalidHex,
+    Storage(StorageError),
+}
+
+/// Whether `s` looks like a hex-encoded hash: non-empty, an even number
+/// of characters, and every character a hex digit.
+///
+/// There's no CoAP server in this tree to receive a `DELETE
+/// /meta/<hash>` path segment from yet, but this is the check such a
+/// handler would need before passing an attacker-controlled path
+/// component through to [`Filesystem::delete_file`].
+pub fn is_valid_hex(s: &str) -> bool {
+    !s.is_empty() && s.len().is_multiple_of(2) && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitOutcome {
+    /// The filesystem was already formatted; existing data was preserved.
+    Ready,
+    /// The filesystem was unformatted or corrupt and has been formatted
+    /// from scratch.
+    Recovered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocateResult {
+    Present,
+    Absent,
+    /// `locate_file` reported a path but it couldn't actually be opened;
+    /// the filesystem entry is inconsistent (e.g. flash corruption) and is
+    /// treated as absent.
+    Inconsistent,
+}
+
+pub struct CertMgr<F> {
+    fs: F,
+    /// Pinned root CAs. Verification must fail closed while this is empty
+    /// rather than treat "no anchors configured" as "trust everything".
+    trust_anchors: heapless::Vec<TrustAnchor, 8>,
+}
+
+impl<F: Filesystem> CertMgr<F> {
+    pub fn new(fs: F) -> Self {
+        Self { fs, trust_anchors: heapless::Vec::new() }
+    }
+
+    /// Detect an unformatted or corrupt filesystem at startup and format
+    /// it, so `locate_file`/`write_file` don't fail in confusing ways on
+    /// first boot or after corruption. A valid, already-formatted
+    /// filesystem is left untouched.
+    pub fn init(&mut self) -> InitOutcome {
+        if self.fs.is_formatted() {
+            InitOutcome::Ready
+        } else {
+            self.fs.format();
+            InitOutcome::Recovered
+        }
+    }
+
+    /// Check whether a metadata hash entry exists for `name`.
+    ///
+    /// `locate_file` alone is not trusted: a corrupted filesystem can
+    /// return a path for an entry that no longer opens. In that case the
+    /// entry is treated as absent rather than present, so callers fall
+    /// back to re-requesting the metadata instead of trusting a dangling
+    /// reference.
+    pub fn have_metadata_hash(&self, name: &str) -> LocateResult {
+        match self.fs.locate_file(name) {
+            None => LocateResult::Absent,
+            Some(path) => match self.fs.open(&path) {
+                Ok(()) => LocateResult::Present,
+                Err(_) => LocateResult::Inconsistent,
+            },
+        }
+    }
+
+    /// Record that metadata for `name` has been fetched and its hash
+    /// pinned, so a later `have_metadata_hash` reports `Present`.
+    ///
+    /// Only presence is recorded: the marker file written to
+    /// `/meta/<name>` is empty. The hash itself doesn't need to be kept
+    /// around on flash because it isn't compared against anything after
+    /// the fact today; if metadata content ever needs re-validating
+    /// against a pinned hash, this is where that hash would need to start
+    /// being stored instead of an empty file.
+    pub fn store_metadata_hash(&mut self, name: &str) -> Result<(), StorageError> {
+        if self.storage_stats().is_full() {
+            return Err(StorageError::Full);
+        }
+
+        let mut path = heapless::String::<64>::new();
+        path.push_str(METADATA_HASH_DIR).map_err(|_| StorageError::Corrupted)?;
+        path.push_str(name).map_err(|_| StorageError::Corrupted)?;
+
+        self.fs.write_file(&path, &[])
+    }
+
+    /// How much of the metadata-hash store is in use, so a caller can
+    /// refuse to fetch a new attester's metadata before even attempting a
+    /// write that [`store_metadata_hash`](Self::store_metadata_hash) would
+    /// reject with [`StorageError::Full`] anyway.
+    pub fn storage_stats(&self) -> StorageStats {
+        StorageStats { used: self.list_metadata_hashes().len(), capacity: METADATA_HASH_CAPACITY }
+    }
+
+    /// Names passed to [`store_metadata_hash`](Self::store_metadata_hash)
+    /// for every marker file currently under `/meta/`, for management
+    /// tooling that needs to audit or prune pinned attesters (e.g. on
+    /// decommission).
+    pub fn list_metadata_hashes(&self) -> heapless::Vec<heapless::String<64>, 8> {
+        self.fs
+            .list_files(METADATA_HASH_DIR)
+            .iter()
+            .filter_map(|path| Some(heapless::String::from(path.strip_prefix(METADATA_HASH_DIR)?)))
+            .collect()
+    }
+
+    /// Delete the marker file for `name`, so a subsequent
+    /// `have_metadata_hash` reports `Absent`. Deleting an entry that was
+    /// never stored is not an error.
+    pub fn remove_metadata_hash(&mut self, name: &str) -> Result<(), StorageError> {
+        let mut path = heapless::String::<64>::new();
+        path.push_str(METADATA_HASH_DIR).map_err(|_| StorageError::Corrupted)?;
+        path.push_str(name).map_err(|_| StorageError::Corrupted)?;
+
+        self.fs.delete_file(&path)
+    }
+
+    /// Like [`Self::remove_metadata_hash`], but for a `name` sourced from
+    /// an untrusted caller (e.g. a future `DELETE /meta/<hash>` CoAP path
+    /// segment): rejects anything that isn't a valid hex string before
+    /// it ever reaches the filesystem layer.
+    pub fn remove_metadata_hash_checked(&mut self, name: &str) -> Result<(), MetadataDeleteError> {
+        if !is_valid_hex(name) {
+            return Err(MetadataDeleteError::InvalidHex);
+        }
+        self.remove_metadata_hash(name).map_err(MetadataDeleteError::Storage)
+    }
+
+    /// (Re-)load pinned trust anchors from `/trust/*` on flash, replacing
+    /// whatever was previously loaded. Called once at startup; EK chain
+    /// verification is meaningless before this has run at least once, and
+    /// deliberately fails closed (rejects everything) while
+    /// `trust_anchors()` is empty rather than falling back to trusting an
+    /// unpinned chain.
+    pub fn load_trust_anchors(&mut self) -> Result<usize, StorageError> {
+        self.trust_anchors.clear();
+        for path in self.fs.list_files(TRUST_ANCHOR_DIR) {
+            let der = self.fs.read_file(&path)?;
+            let fp = fingerprint(&der);
+            // A full device only has room for as many anchors as fit in
+            // `trust_anchors`; silently dropping the rest would leave EK
+            // verification passing for attesters signed under a root that
+            // was actually provisioned but didn't fit.
+            self.trust_anchors.push(TrustAnchor { der, fingerprint: fp }).map_err(|_| StorageError::Corrupted)?;
+        }
+        Ok(self.trust_anchors.len())
+    }
+
+    /// Write a new DER-encoded root CA to `/trust/<name>` and make it
+    /// trusted immediately, without requiring a re-`load_trust_anchors`.
+    pub fn add_trust_anchor(&mut self, name: &str, der: &[u8]) -> Result<(), StorageError> {
+        let mut path = heapless::String::<64>::new();
+        path.push_str(TRUST_ANCHOR_DIR).map_err(|_| StorageError::Corrupted)?;
+        path.push_str(name).map_err(|_| StorageError::Corrupted)?;
+
+        self.fs.write_file(&path, der)?;
+
+        let mut stored = heapless::Vec::new();
+        stored.extend_from_slice(der).map_err(|_| StorageError::Corrupted)?;
+        self.trust_anchors
+            .push(TrustAnchor { der: stored, fingerprint: fingerprint(der) })
+            .map_err(|_| StorageError::Corrupted)
+    }
+
+    /// Currently pinned trust anchors. Empty means EK verification must
+    /// fail closed: no anchors were ever loaded (or `/trust/` is empty),
+    /// which almost certainly means the device hasn't been provisioned.
+    pub fn trust_anchors(&self) -> &[TrustAnchor] {
+        &self.trust_anchors
+    }
+
+    /// Verify an attester's EK certificate: `trust_anchor_der` (the root
+    /// the caller walked the chain up to) must actually be one of
+    /// `trust_anchors()` — matched by fingerprint, the same check
+    /// `add_trust_anchor`/`load_trust_anchors` populate — rather than
+    /// trusted just because the caller claims it's the root; the chain
+    /// must then reach `trust_anchor_subject` (there's no X.509 parser in
+    /// this tree to pull a subject back out of `trust_anchor_der` itself,
+    /// see [`super::chain`], so the caller supplies it directly); and the
+    /// leaf must finally pass `policy`.
+    ///
+    /// Fails closed with `EkVerifyError::NoTrustAnchors` if
+    /// `load_trust_anchors`/`add_trust_anchor` has never pinned anything:
+    /// an unprovisioned device must reject every attester rather than
+    /// fall back to trusting an unpinned chain. Fails with
+    /// `EkVerifyError::UnpinnedTrustAnchor` if `trust_anchor_der` doesn't
+    /// match any pinned anchor's fingerprint, so a caller can't walk the
+    /// chain to an arbitrary self-declared root and have it accepted.
+    pub fn verify_ek_chain<V: ChainVerifier, P: EkPolicy>(
+        &self,
+        verifier: &V,
+        leaf: &Certificate,
+        intermediates: &[Certificate],
+        trust_anchor_der: &[u8],
+        trust_anchor_subject: &[u8],
+        policy: &P,
+    ) -> Result<(), EkVerifyError> {
+        if self.trust_anchors.is_empty() {
+            return Err(EkVerifyError::NoTrustAnchors);
+        }
+
+        let anchor_fingerprint = fingerprint(trust_anchor_der);
+        if !self.trust_anchors.iter().any(|anchor| anchor.fingerprint == anchor_fingerprint) {
+            return Err(EkVerifyError::UnpinnedTrustAnchor);
+        }
+
+        verify_chain(verifier, leaf, intermediates, trust_anchor_subject).map_err(EkVerifyError::Chain)?;
+        policy.check(leaf).map_err(EkVerifyError::Policy)
+    }
+}
+
+/// Failure reason from [`CertMgr::verify_ek_chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EkVerifyError {
+    /// No trust anchor has ever been loaded; see
+    /// [`CertMgr::load_trust_anchors`].
+    NoTrustAnchors,
+    /// `trust_anchor_der` doesn't match any pinned anchor's fingerprint.
+    UnpinnedTrustAnchor,
+    Chain(ChainError),
+    Policy(PolicyError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeFs {
+        locate: Option<&'static str>,
+        open_fails: bool,
+        formatted: bool,
+        files: heapless::Vec<(heapless::String<64>, heapless::Vec<u8, 1024>), 8>,
+    }
+
+    impl FakeFs {
+        fn new() -> Self {
+            Self { locate: None, open_fails: false, formatted: true, files: heapless::Vec::new() }
+        }
+    }
+
+    impl Filesystem for FakeFs {
+        fn locate_file(&self, _name: &str) -> Option<heapless::String<64>> {
+            self.locate.map(heapless::String::from)
+        }
+
+        fn open(&self, _path: &str) -> Result<(), StorageError> {
+            if self.open_fails {
+                Err(StorageError::Corrupted)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn is_formatted(&self) -> bool {
+            self.formatted
+        }
+
+        fn format(&mut self) {
+            self.formatted = true;
+        }
+
+        fn list_files(&self, prefix: &str) -> heapless::Vec<heapless::String<64>, 8> {
+            self.files.iter().filter(|(p, _)| p.starts_with(prefix)).map(|(p, _)| p.clone()).collect()
+        }
+
+        fn read_file(&self, path: &str) -> Result<heapless::Vec<u8, 1024>, StorageError> {
+            self.files
+                .iter()
+                .find(|(p, _)| p == path)
+                .map(|(_, data)| data.clone())
+                .ok_or(StorageError::NotFound)
+        }
+
+        fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+            let mut stored = heapless::Vec::new();
+            stored.extend_from_slice(data).map_err(|_| StorageError::Corrupted)?;
+            self.files.push((heapless::String::from(path), stored)).map_err(|_| StorageError::Corrupted)
+        }
+
+        fn delete_file(&mut self, path: &str) -> Result<(), StorageError> {
+            self.files.retain(|(p, _)| p != path);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn absent_when_not_located() {
+        let mgr = CertMgr::new(FakeFs { locate: None, ..FakeFs::new() });
+        assert_eq!(mgr.have_metadata_hash("attester-1"), LocateResult::Absent);
+    }
+
+    #[test]
+    fn present_when_located_and_opens() {
+        let mgr = CertMgr::new(FakeFs { locate: Some("/meta/attester-1"), ..FakeFs::new() });
+        assert_eq!(mgr.have_metadata_hash("attester-1"), LocateResult::Present);
+    }
+
+    #[test]
+    fn inconsistent_when_locate_succeeds_but_open_fails() {
+        let mgr = CertMgr::new(FakeFs { locate: Some("/meta/attester-1"), open_fails: true, ..FakeFs::new() });
+        assert_eq!(mgr.have_metadata_hash("attester-1"), LocateResult::Inconsistent);
+    }
+
+    #[test]
+    fn unformatted_filesystem_is_formatted_on_init() {
+        let mut mgr = CertMgr::new(FakeFs { formatted: false, ..FakeFs::new() });
+        assert_eq!(mgr.init(), InitOutcome::Recovered);
+        assert!(mgr.fs.is_formatted());
+    }
+
+    #[test]
+    fn already_formatted_filesystem_is_left_untouched() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        assert_eq!(mgr.init(), InitOutcome::Ready);
+    }
+
+    #[test]
+    fn no_trust_anchor_is_pinned_before_any_is_loaded() {
+        let mgr = CertMgr::new(FakeFs::new());
+        assert!(mgr.trust_anchors().is_empty());
+    }
+
+    #[test]
+    fn load_trust_anchors_reads_every_der_file_under_trust() {
+        let mut fs = FakeFs::new();
+        fs.write_file("/trust/root-0.der", b"root-cert-0").unwrap();
+        fs.write_file("/trust/root-1.der", b"root-cert-1").unwrap();
+        let mut mgr = CertMgr::new(fs);
+
+        assert_eq!(mgr.load_trust_anchors(), Ok(2));
+        assert_eq!(mgr.trust_anchors().len(), 2);
+    }
+
+    /// Trusts a signature iff it was recorded as valid for that exact
+    /// (tbs, signature, issuer_subject) triple, mirroring `chain::tests`'
+    /// `FakeVerifier`.
+    struct FakeVerifier {
+        valid: heapless::Vec<(&'static [u8], &'static [u8], &'static [u8]), 8>,
+    }
+
+    impl ChainVerifier for FakeVerifier {
+        fn verify_signed_by(&self, tbs: &[u8], signature: &[u8], issuer_subject: &[u8]) -> bool {
+            self.valid.iter().any(|(t, s, i)| *t == tbs && *s == signature && *i == issuer_subject)
+        }
+    }
+
+    fn self_signed_root(subject: &'static [u8]) -> Certificate<'static> {
+        Certificate { subject, issuer: subject, is_ca: true, key_cert_sign: true, tbs: subject, signature: b"root-sig" }
+    }
+
+    #[test]
+    fn verify_ek_chain_fails_closed_with_no_trust_anchors() {
+        let mgr = CertMgr::new(FakeFs::new());
+        let root = self_signed_root(b"device-root");
+        let verifier = FakeVerifier { valid: heapless::Vec::new() };
+
+        assert_eq!(
+            mgr.verify_ek_chain(&verifier, &root, &[], b"device-root-der", b"device-root", &crate::certmgr::AcceptAll),
+            Err(EkVerifyError::NoTrustAnchors)
+        );
+    }
+
+    #[test]
+    fn verify_ek_chain_rejects_a_trust_anchor_der_that_was_never_pinned() {
+        let mut fs = FakeFs::new();
+        fs.write_file("/trust/root.der", b"device-root-der").unwrap();
+        let mut mgr = CertMgr::new(fs);
+        mgr.load_trust_anchors().unwrap();
+
+        let root = self_signed_root(b"device-root");
+        let mut valid = heapless::Vec::new();
+        valid.push((b"device-root".as_slice(), b"root-sig".as_slice(), b"device-root".as_slice())).ok();
+        let verifier = FakeVerifier { valid };
+
+        assert_eq!(
+            mgr.verify_ek_chain(&verifier, &root, &[], b"some-other-der", b"device-root", &crate::certmgr::AcceptAll),
+            Err(EkVerifyError::UnpinnedTrustAnchor)
+        );
+    }
+
+    #[test]
+    fn verify_ek_chain_accepts_a_leaf_reaching_a_pinned_anchor() {
+        let mut fs = FakeFs::new();
+        fs.write_file("/trust/root.der", b"device-root-der").unwrap();
+        let mut mgr = CertMgr::new(fs);
+        mgr.load_trust_anchors().unwrap();
+
+        let root = self_signed_root(b"device-root");
+        let mut valid = heapless::Vec::new();
+        valid.push((b"device-root".as_slice(), b"root-sig".as_slice(), b"device-root".as_slice())).ok();
+        let verifier = FakeVerifier { valid };
+
+        assert_eq!(
+            mgr.verify_ek_chain(&verifier, &root, &[], b"device-root-der", b"device-root", &crate::certmgr::AcceptAll),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn verify_ek_chain_propagates_a_policy_rejection() {
+        use crate::certmgr::{fingerprint, ManufacturerAllowlist, PolicyError};
+
+        let mut fs = FakeFs::new();
+        fs.write_file("/trust/root.der", b"device-root-der").unwrap();
+        let mut mgr = CertMgr::new(fs);
+        mgr.load_trust_anchors().unwrap();
+
+        let root = self_signed_root(b"device-root");
+        let mut valid = heapless::Vec::new();
+        valid.push((b"device-root".as_slice(), b"root-sig".as_slice(), b"device-root".as_slice())).ok();
+        let verifier = FakeVerifier { valid };
+
+        let mut policy: ManufacturerAllowlist<1> = ManufacturerAllowlist::new();
+        policy.allow(fingerprint(b"some-other-cert")).unwrap();
+
+        assert_eq!(
+            mgr.verify_ek_chain(&verifier, &root, &[], b"device-root-der", b"device-root", &policy),
+            Err(EkVerifyError::Policy(PolicyError::NotAllowlisted))
+        );
+    }
+
+    #[test]
+    fn store_metadata_hash_writes_an_empty_marker_file() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+
+        mgr.store_metadata_hash("attester-1").unwrap();
+
+        assert_eq!(mgr.fs.read_file("/meta/attester-1").unwrap().as_slice(), &[] as &[u8]);
+    }
+
+    #[test]
+    fn list_metadata_hashes_returns_every_stored_name() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        mgr.store_metadata_hash("attester-1").unwrap();
+        mgr.store_metadata_hash("attester-2").unwrap();
+
+        let mut names = mgr.list_metadata_hashes();
+        names.sort();
+
+        assert_eq!(names.iter().map(|n| n.as_str()).collect::<heapless::Vec<_, 8>>().as_slice(), ["attester-1", "attester-2"]);
+    }
+
+    #[test]
+    fn list_metadata_hashes_is_empty_when_nothing_was_stored() {
+        let mgr = CertMgr::new(FakeFs::new());
+        assert!(mgr.list_metadata_hashes().is_empty());
+    }
+
+    #[test]
+    fn remove_metadata_hash_deletes_the_marker_file() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        mgr.store_metadata_hash("attester-1").unwrap();
+
+        mgr.remove_metadata_hash("attester-1").unwrap();
+
+        assert!(mgr.list_metadata_hashes().is_empty());
+    }
+
+    #[test]
+    fn remove_metadata_hash_on_a_missing_entry_is_not_an_error() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        assert_eq!(mgr.remove_metadata_hash("never-stored"), Ok(()));
+    }
+
+    #[test]
+    fn storage_stats_reports_used_and_capacity() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        mgr.store_metadata_hash("attester-1").unwrap();
+
+        let stats = mgr.storage_stats();
+        assert_eq!(stats.used, 1);
+        assert_eq!(stats.capacity, METADATA_HASH_CAPACITY);
+        assert!(!stats.is_full());
+    }
+
+    #[test]
+    fn store_metadata_hash_fails_gracefully_once_full() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        for i in 0..METADATA_HASH_CAPACITY {
+            let mut name = heapless::String::<8>::new();
+            name.push_str("a").unwrap();
+            name.push((b'0' + i as u8) as char).unwrap();
+            mgr.store_metadata_hash(&name).unwrap();
+        }
+
+        assert!(mgr.storage_stats().is_full());
+        assert_eq!(mgr.store_metadata_hash("one-too-many"), Err(StorageError::Full));
+    }
+
+    #[test]
+    fn is_valid_hex_accepts_lowercase_hex_pairs() {
+        assert!(is_valid_hex("deadbeef"));
+    }
+
+    #[test]
+    fn is_valid_hex_rejects_odd_length_empty_and_non_hex() {
+        assert!(!is_valid_hex("abc"));
+        assert!(!is_valid_hex(""));
+        assert!(!is_valid_hex("zz"));
+    }
+
+    #[test]
+    fn remove_metadata_hash_checked_rejects_a_non_hex_name() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        assert_eq!(mgr.remove_metadata_hash_checked("../trust"), Err(MetadataDeleteError::InvalidHex));
+    }
+
+    #[test]
+    fn remove_metadata_hash_checked_deletes_a_valid_hex_entry() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+        mgr.store_metadata_hash("deadbeef").unwrap();
+
+        assert_eq!(mgr.remove_metadata_hash_checked("deadbeef"), Ok(()));
+        assert!(mgr.list_metadata_hashes().is_empty());
+    }
+
+    #[test]
+    fn add_trust_anchor_persists_to_flash_and_trusts_immediately() {
+        let mut mgr = CertMgr::new(FakeFs::new());
+
+        mgr.add_trust_anchor("root-0.der", b"root-cert-0").unwrap();
+
+        assert_eq!(mgr.trust_anchors().len(), 1);
+        assert_eq!(mgr.fs.read_file("/trust/root-0.der").unwrap().as_slice(), b"root-cert-0");
+    }
+}
@@ -0,0 +1,70 @@
+//! Certificate validity-window checks.
+//!
+//! The device has a monotonic millisecond clock (`get_time_ms`) but no
+//! wall clock, so a notBefore/notAfter check is only meaningful once some
+//! other source (the attester's own clock, a provisioning-time stamp, ...)
+//! has supplied a trusted point in wall-clock time. Without one, the check
+//! is skipped rather than silently treated as passing.
+
+/// A certificate's validity window, in Unix milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidityWindow {
+    pub not_before_ms: u64,
+    pub not_after_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidityError {
+    NotYetValid,
+    Expired,
+}
+
+/// Check `window` against `trusted_time_ms`, if one is available.
+///
+/// Returns `Ok(())` both when the certificate is within its window and
+/// when no trusted time was supplied; callers should distinguish the two
+/// by checking whether `trusted_time_ms` was `None` and log a warning in
+/// that case, rather than treat a skipped check the same as a passed one.
+pub fn check_validity(window: &ValidityWindow, trusted_time_ms: Option<u64>) -> Result<(), ValidityError> {
+    let now_ms = match trusted_time_ms {
+        None => return Ok(()),
+        Some(t) => t,
+    };
+
+    if now_ms < window.not_before_ms {
+        Err(ValidityError::NotYetValid)
+    } else if now_ms > window.not_after_ms {
+        Err(ValidityError::Expired)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> ValidityWindow {
+        ValidityWindow { not_before_ms: 1_000, not_after_ms: 2_000 }
+    }
+
+    #[test]
+    fn accepts_a_time_inside_the_window() {
+        assert_eq!(check_validity(&window(), Some(1_500)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_time_before_not_before() {
+        assert_eq!(check_validity(&window(), Some(999)), Err(ValidityError::NotYetValid));
+    }
+
+    #[test]
+    fn rejects_a_time_after_not_after() {
+        assert_eq!(check_validity(&window(), Some(2_001)), Err(ValidityError::Expired));
+    }
+
+    #[test]
+    fn skips_the_check_when_no_trusted_time_is_available() {
+        assert_eq!(check_validity(&window(), None), Ok(()));
+    }
+}
@@ -0,0 +1,61 @@
+//! Runtime configuration for [`super::FobnailClient`].
+
+use crate::tpm::Name;
+
+/// A pre-loaded AIK that is trusted without going through credential
+/// activation, identified by its TPM name.
+///
+/// This is meant for deployments where the fleet of attesters is known
+/// ahead of time, so the expensive `MakeCredential`/`ActivateCredential`
+/// challenge round-trip can be skipped for names that are already trusted.
+#[derive(Debug, Clone)]
+pub struct TrustedAik {
+    pub name: Name,
+    pub label: heapless::String<32>,
+}
+
+/// Consecutive failures after which `FobnailClient` gives up on an
+/// attester and moves to `State::Failed`, if `ClientConfig::max_retries`
+/// isn't set explicitly.
+const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Configuration accepted by [`super::FobnailClient::new`].
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// AIKs that skip credential activation when their name matches.
+    ///
+    /// Full activation remains the default: an attester whose AIK name is
+    /// not in this list always goes through the normal challenge.
+    pub trusted_aiks: heapless::Vec<TrustedAik, 8>,
+    /// How many consecutive failures (timeouts, failed AIK challenges,
+    /// ...) `FobnailClient` accepts: earlier failures are retried, and the
+    /// one that brings the count to `max_retries` moves to
+    /// `State::Failed`.
+    pub max_retries: u32,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self { trusted_aiks: heapless::Vec::new(), max_retries: DEFAULT_MAX_RETRIES }
+    }
+}
+
+impl ClientConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-load a known-good AIK, identified by its TPM name.
+    ///
+    /// Returns `false` if the trusted AIK table is full.
+    pub fn add_trusted_aik(&mut self, name: Name, label: &str) -> bool {
+        let mut s = heapless::String::new();
+        let _ = s.push_str(label);
+        self.trusted_aiks.push(TrustedAik { name, label: s }).is_ok()
+    }
+
+    /// Look up a pre-loaded AIK by name.
+    pub fn find_trusted_aik(&self, name: &Name) -> Option<&TrustedAik> {
+        self.trusted_aiks.iter().find(|t| &t.name == name)
+    }
+}
@@ -0,0 +1,47 @@
+//! Constant-time comparison for attestation secrets.
+//!
+//! A naive `==` on slices short-circuits at the first mismatching byte,
+//! which leaks how many leading bytes an attacker guessed correctly
+//! through response timing. Everything that gates trust on comparing a
+//! secret against an attacker-influenced value (e.g. the decrypted AIK
+//! challenge secret) must go through this instead.
+
+/// Compare two byte slices without branching on the position of the first
+/// difference. Unequal lengths are rejected up front since there's no
+/// secret-dependent branch to avoid there, only the byte-by-byte
+/// comparison itself needs to run in constant time.
+pub fn ct_eq(expected: &[u8], actual: &[u8]) -> bool {
+    if expected.len() != actual.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (a, b) in expected.iter().zip(actual.iter()) {
+        diff |= a ^ b;
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_inputs_compare_equal() {
+        assert!(ct_eq(b"attestation-secret", b"attestation-secret"));
+    }
+
+    #[test]
+    fn differing_inputs_compare_unequal() {
+        assert!(!ct_eq(b"attestation-secret", b"attestation-wrong!"));
+    }
+
+    #[test]
+    fn differing_lengths_compare_unequal() {
+        assert!(!ct_eq(b"short", b"much-longer-secret"));
+    }
+
+    #[test]
+    fn empty_inputs_compare_equal() {
+        assert!(ct_eq(b"", b""));
+    }
+}
@@ -0,0 +1,8 @@
+//! Cryptographic glue specific to the client state machine (as opposed to
+//! `crate::crypto`, which holds primitives with no dependency on Trussed).
+
+pub mod ct_eq;
+pub mod rng;
+
+pub use ct_eq::ct_eq;
+pub use rng::TrussedRng;
@@ -0,0 +1,289 @@
+//! Randomness sourced from Trussed's `random_bytes` syscall.
+//!
+//! `random_bytes` can fail two different ways: the RNG is temporarily
+//! busy replenishing its entropy pool (retryable), or it has failed
+//! outright (fatal). Previously both were logged as a generic "Failed to
+//! generate" and dropped the whole operation back to `Idle`, wasting a
+//! full retry cycle on what was often just a transient exhaustion.
+
+/// Outcome of a single `random_bytes` syscall attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RngError {
+    /// Entropy pool temporarily exhausted; retrying shortly is expected
+    /// to succeed.
+    Busy,
+    /// The RNG backend itself failed; retrying won't help.
+    Fatal,
+}
+
+/// Trussed's `random_bytes` syscall, abstracted so retry logic can be
+/// tested without a real Trussed client.
+pub trait RandomBytesSource {
+    /// Request up to `buf.len()` random bytes, returning how many were
+    /// actually written (may be fewer than requested).
+    fn random_bytes(&mut self, buf: &mut [u8]) -> Result<usize, RngError>;
+}
+
+/// Maximum number of retries for a `Busy` result before giving up.
+const MAX_BUSY_RETRIES: u32 = 3;
+
+/// Fill `dest` with random bytes, retrying a bounded number of times on a
+/// transient `Busy` result. A `Fatal` result, or exhausting the retry
+/// budget, is returned immediately.
+pub fn fill_bytes_with_retry(
+    source: &mut impl RandomBytesSource,
+    dest: &mut [u8],
+) -> Result<(), RngError> {
+    let mut filled = 0;
+    let mut retries = 0;
+
+    while filled < dest.len() {
+        match source.random_bytes(&mut dest[filled..]) {
+            Ok(n) => {
+                filled += n;
+                retries = 0;
+            }
+            Err(RngError::Busy) => {
+                if retries >= MAX_BUSY_RETRIES {
+                    return Err(RngError::Busy);
+                }
+                retries += 1;
+            }
+            Err(RngError::Fatal) => return Err(RngError::Fatal),
+        }
+    }
+
+    Ok(())
+}
+
+/// Minimum number of bytes `TrussedRng` asks for per `random_bytes`
+/// syscall. RSA key generation and similar callers mix a few
+/// full-size requests with many tiny ones (`next_u32`/`next_u64`, used
+/// internally by prime search); without batching, each of those tiny
+/// reads would round-trip to Trussed on its own.
+const RNG_BATCH_SIZE: usize = 128;
+
+/// `rand_core::RngCore` (and `CryptoRng`) over a Trussed `random_bytes`
+/// syscall, so Trussed-backed randomness can be handed directly to
+/// crates (e.g. `rsa`) that require a `CryptoRng + RngCore` source.
+/// `next_u32`/`next_u64` assemble their output from `fill_bytes`,
+/// little-endian. Requests smaller than `RNG_BATCH_SIZE` are served out
+/// of an internal cache that's refilled `RNG_BATCH_SIZE` bytes at a time,
+/// rather than issuing a syscall per request.
+pub struct TrussedRng<S> {
+    source: S,
+    cache: [u8; RNG_BATCH_SIZE],
+    cache_pos: usize,
+    cache_len: usize,
+}
+
+impl<S: RandomBytesSource> TrussedRng<S> {
+    pub fn new(source: S) -> Self {
+        Self { source, cache: [0u8; RNG_BATCH_SIZE], cache_pos: 0, cache_len: 0 }
+    }
+
+    fn fill_bytes_batched(&mut self, mut dest: &mut [u8]) -> Result<(), RngError> {
+        while !dest.is_empty() {
+            let cached = self.cache_len - self.cache_pos;
+            if cached > 0 {
+                let n = cached.min(dest.len());
+                dest[..n].copy_from_slice(&self.cache[self.cache_pos..self.cache_pos + n]);
+                self.cache_pos += n;
+                dest = &mut dest[n..];
+                continue;
+            }
+
+            if dest.len() >= RNG_BATCH_SIZE {
+                // Already a big enough request on its own; no cache
+                // round-trip needed.
+                let len = dest.len();
+                fill_bytes_with_retry(&mut self.source, &mut dest[..len])?;
+                return Ok(());
+            }
+
+            fill_bytes_with_retry(&mut self.source, &mut self.cache)?;
+            self.cache_pos = 0;
+            self.cache_len = RNG_BATCH_SIZE;
+        }
+        Ok(())
+    }
+}
+
+impl<S: RandomBytesSource> rand_core::RngCore for TrussedRng<S> {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // `RngCore::fill_bytes` has no way to report failure; a Trussed
+        // RNG failure here means the device's entropy source is broken,
+        // which callers (key generation, AIK challenges) can't proceed
+        // past anyway.
+        self.fill_bytes_batched(dest).expect("Trussed random_bytes failed");
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes_batched(dest).map_err(|e| {
+            // `rand_core::Error::new` takes an arbitrary boxed error but is
+            // only available under the `std` feature; this crate is
+            // `#![no_std]` outside `cfg(test)` (see `src/lib.rs`), so the
+            // failure is instead reported as a custom, no_std-safe code.
+            let code = match e {
+                RngError::Busy => TRUSSED_RNG_BUSY_CODE,
+                RngError::Fatal => TRUSSED_RNG_FATAL_CODE,
+            };
+            rand_core::Error::from(code)
+        })
+    }
+}
+
+/// `rand_core::Error` custom-code range starts at `Error::CUSTOM_START`;
+/// these two are this crate's only `try_fill_bytes` failure modes.
+const TRUSSED_RNG_BUSY_CODE: core::num::NonZeroU32 =
+    match core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START) {
+        Some(v) => v,
+        None => panic!("Error::CUSTOM_START is nonzero"),
+    };
+const TRUSSED_RNG_FATAL_CODE: core::num::NonZeroU32 =
+    match core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START + 1) {
+        Some(v) => v,
+        None => panic!("Error::CUSTOM_START is nonzero"),
+    };
+
+/// RSA key generation (and other crates expecting a secure RNG) require
+/// `CryptoRng`, a marker trait with no methods of its own, on top of
+/// `RngCore`. Trussed's `random_bytes` is the device's actual entropy
+/// source, so asserting this is correct rather than aspirational.
+impl<S: RandomBytesSource> rand_core::CryptoRng for TrussedRng<S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::RngCore;
+
+    struct FlakyRng {
+        busy_countdown: u32,
+        fatal: bool,
+    }
+
+    impl RandomBytesSource for FlakyRng {
+        fn random_bytes(&mut self, buf: &mut [u8]) -> Result<usize, RngError> {
+            if self.fatal {
+                return Err(RngError::Fatal);
+            }
+            if self.busy_countdown > 0 {
+                self.busy_countdown -= 1;
+                return Err(RngError::Busy);
+            }
+            for b in buf.iter_mut() {
+                *b = 0x42;
+            }
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn transient_busy_is_retried_until_success() {
+        let mut rng = FlakyRng { busy_countdown: 2, fatal: false };
+        let mut dest = [0u8; 8];
+
+        fill_bytes_with_retry(&mut rng, &mut dest).unwrap();
+
+        assert_eq!(dest, [0x42; 8]);
+    }
+
+    #[test]
+    fn fatal_failure_is_not_retried() {
+        let mut rng = FlakyRng { busy_countdown: 0, fatal: true };
+        let mut dest = [0u8; 8];
+
+        assert_eq!(fill_bytes_with_retry(&mut rng, &mut dest), Err(RngError::Fatal));
+    }
+
+    #[test]
+    fn busy_retry_budget_is_bounded() {
+        let mut rng = FlakyRng { busy_countdown: 100, fatal: false };
+        let mut dest = [0u8; 8];
+
+        assert_eq!(fill_bytes_with_retry(&mut rng, &mut dest), Err(RngError::Busy));
+    }
+
+    #[test]
+    fn trussed_rng_fill_bytes_uses_random_bytes_syscall() {
+        let mut rng = TrussedRng::new(FlakyRng { busy_countdown: 0, fatal: false });
+        let mut dest = [0u8; 16];
+        rng.fill_bytes(&mut dest);
+        assert_eq!(dest, [0x42; 16]);
+    }
+
+    #[test]
+    fn trussed_rng_next_u32_is_little_endian_over_fill_bytes() {
+        let mut rng = TrussedRng::new(FlakyRng { busy_countdown: 0, fatal: false });
+        assert_eq!(rng.next_u32(), u32::from_le_bytes([0x42; 4]));
+    }
+
+    #[test]
+    fn trussed_rng_try_fill_bytes_reports_fatal_failure() {
+        let mut rng = TrussedRng::new(FlakyRng { busy_countdown: 0, fatal: true });
+        let mut dest = [0u8; 4];
+        assert!(rng.try_fill_bytes(&mut dest).is_err());
+    }
+
+    struct CountingRng {
+        calls: u32,
+    }
+
+    impl RandomBytesSource for CountingRng {
+        fn random_bytes(&mut self, buf: &mut [u8]) -> Result<usize, RngError> {
+            self.calls += 1;
+            for b in buf.iter_mut() {
+                *b = 0x42;
+            }
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn small_requests_are_served_from_one_batched_syscall() {
+        let mut rng = TrussedRng::new(CountingRng { calls: 0 });
+
+        for _ in 0..RNG_BATCH_SIZE / 4 {
+            rng.next_u32();
+        }
+
+        assert_eq!(rng.source.calls, 1);
+    }
+
+    #[test]
+    fn a_request_at_least_as_big_as_the_batch_size_bypasses_the_cache() {
+        let mut rng = TrussedRng::new(CountingRng { calls: 0 });
+        let mut dest = [0u8; RNG_BATCH_SIZE];
+
+        rng.fill_bytes(&mut dest);
+
+        assert_eq!(rng.source.calls, 1);
+        assert_eq!(dest, [0x42; RNG_BATCH_SIZE]);
+    }
+
+    #[test]
+    fn cache_is_refilled_once_exhausted() {
+        let mut rng = TrussedRng::new(CountingRng { calls: 0 });
+        let mut dest = [0u8; 4];
+
+        // One batch serves exactly RNG_BATCH_SIZE / 4 of these; the next
+        // one finds the cache empty and has to refill it.
+        for _ in 0..RNG_BATCH_SIZE / 4 + 1 {
+            rng.fill_bytes(&mut dest);
+        }
+
+        assert_eq!(rng.source.calls, 2);
+    }
+}
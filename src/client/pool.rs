@@ -0,0 +1,141 @@
+//! Running attestation against several attesters at once.
+//!
+//! `FobnailClient` itself only ever tracks one conversation: a single
+//! `state`, a single `pending_request`. A rack of attesters bridged over
+//! the same link needs one such state machine per attester, each advancing
+//! independently as its own responses arrive. `SessionPool` is that: a
+//! small fixed-capacity table from session id (see
+//! [`super::session::Session`], whose `id` is the same kind of handle) to
+//! its own `FobnailClient`.
+//!
+//! Because each session gets a fully separate `FobnailClient`, there's no
+//! shared key material for one session's cleanup to accidentally reach
+//! into: `abort()` on one only ever zeroes the `expected_secret` living
+//! inside *that* client's own `state`.
+
+use super::{ClientConfig, FobnailClient};
+
+/// How many attesters can be tracked concurrently. Sized for a small rack
+/// bridged over one USB-Ethernet link, not an arbitrary fleet.
+const MAX_SESSIONS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolError {
+    /// Already tracking `MAX_SESSIONS` attesters; a new one has to wait
+    /// for an existing session to `close()` first.
+    Full,
+}
+
+/// A table of independent [`FobnailClient`]s, one per attester session.
+pub struct SessionPool {
+    sessions: heapless::Vec<(u32, FobnailClient), MAX_SESSIONS>,
+}
+
+impl SessionPool {
+    pub fn new() -> Self {
+        Self { sessions: heapless::Vec::new() }
+    }
+
+    /// Start tracking a new attester under `session_id`, with its own
+    /// `FobnailClient` beginning at `State::Idle`. A `session_id` already
+    /// open is left untouched, so a duplicate reconnect notification
+    /// doesn't reset an attester mid-enrollment.
+    pub fn open(&mut self, session_id: u32, config: ClientConfig) -> Result<(), PoolError> {
+        if self.get_mut(session_id).is_some() {
+            return Ok(());
+        }
+        self.sessions.push((session_id, FobnailClient::new(config, None))).map_err(|_| PoolError::Full)
+    }
+
+    /// The `FobnailClient` driving `session_id`'s conversation, if it's
+    /// currently open.
+    pub fn get_mut(&mut self, session_id: u32) -> Option<&mut FobnailClient> {
+        self.sessions.iter_mut().find(|(id, _)| *id == session_id).map(|(_, client)| client)
+    }
+
+    /// Stop tracking `session_id`, e.g. once its attestation reaches
+    /// `State::Done`/`State::Failed` or the underlying connection drops.
+    /// `abort()`s the client first so any in-flight AIK challenge secret
+    /// is scrubbed rather than left for whatever reuses the memory next;
+    /// every other open session is unaffected.
+    pub fn close(&mut self, session_id: u32) {
+        if let Some(client) = self.get_mut(session_id) {
+            client.abort();
+        }
+        self.sessions.retain(|(id, _)| *id != session_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+impl Default for SessionPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClientConfig, State};
+
+    #[test]
+    fn opening_a_session_starts_it_at_idle() {
+        let mut pool = SessionPool::new();
+        pool.open(1, ClientConfig::new()).unwrap();
+
+        assert_eq!(pool.get_mut(1).unwrap().state(), &State::Idle);
+    }
+
+    #[test]
+    fn sessions_advance_independently() {
+        let mut pool = SessionPool::new();
+        pool.open(1, ClientConfig::new()).unwrap();
+        pool.open(2, ClientConfig::new()).unwrap();
+
+        pool.get_mut(1).unwrap().enter_credential_activation_wait(0);
+
+        assert_eq!(pool.get_mut(1).unwrap().state(), &State::VerifyAikStage1);
+        assert_eq!(pool.get_mut(2).unwrap().state(), &State::Idle);
+    }
+
+    #[test]
+    fn reopening_an_already_open_session_does_not_reset_it() {
+        let mut pool = SessionPool::new();
+        pool.open(1, ClientConfig::new()).unwrap();
+        pool.get_mut(1).unwrap().enter_credential_activation_wait(0);
+
+        pool.open(1, ClientConfig::new()).unwrap();
+
+        assert_eq!(pool.get_mut(1).unwrap().state(), &State::VerifyAikStage1);
+    }
+
+    #[test]
+    fn closing_a_session_removes_only_that_session() {
+        let mut pool = SessionPool::new();
+        pool.open(1, ClientConfig::new()).unwrap();
+        pool.open(2, ClientConfig::new()).unwrap();
+
+        pool.close(1);
+
+        assert!(pool.get_mut(1).is_none());
+        assert!(pool.get_mut(2).is_some());
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn pool_reports_full_once_at_capacity() {
+        let mut pool = SessionPool::new();
+        for id in 0..MAX_SESSIONS as u32 {
+            pool.open(id, ClientConfig::new()).unwrap();
+        }
+
+        assert_eq!(pool.open(MAX_SESSIONS as u32, ClientConfig::new()), Err(PoolError::Full));
+    }
+}
@@ -0,0 +1,341 @@
+//! Persisting a completed EK↔AIK association across reboots, so a token
+//! that already provisioned an attester doesn't have to re-run EK/AIK
+//! verification (`VerifyAikStage1`/`VerifyAikStage2`) after every power
+//! cycle.
+
+use crate::certmgr::{Filesystem, StorageError};
+use crate::tpm::aik::AikPublicKey;
+
+/// Path a provisioning record is stored under. There's only ever one, for
+/// the single attester `FobnailClient` talks to at a time.
+pub const PROVISION_PATH: &str = "/provision/state";
+
+/// The EK hash and AIK a completed attestation trusted, serialized to
+/// flash so it can be re-checked against whichever attester connects on
+/// the next boot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvisioningRecord {
+    pub ek_hash: [u8; 32],
+    pub aik_pubkey: AikPublicKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    Truncated,
+    /// The modulus length field claims more bytes than `AikPublicKey::Rsa`
+    /// can hold, so this can't be a record this build ever wrote.
+    ModulusTooLarge,
+    UnknownAikTag(u8),
+}
+
+/// Tag byte identifying which `AikPublicKey` variant follows, mirroring
+/// how [`crate::client::tpm::LoadedKeyName`] tags its algorithm.
+const AIK_TAG_RSA: u8 = 0;
+const AIK_TAG_ED25519: u8 = 1;
+
+/// Encode `pubkey` into `out` using the tag layout documented on
+/// [`ProvisioningRecord::encode`]. Shared by `ProvisioningRecord` and the
+/// per-EK AIK cache below so both stores agree on one wire format.
+fn encode_aik_pubkey(pubkey: &AikPublicKey, out: &mut heapless::Vec<u8, 1024>) {
+    match pubkey {
+        #[cfg(feature = "rsa")]
+        AikPublicKey::Rsa { modulus, exponent } => {
+            out.push(AIK_TAG_RSA).ok();
+            out.extend_from_slice(&(modulus.len() as u16).to_be_bytes()).ok();
+            out.extend_from_slice(modulus).ok();
+            out.extend_from_slice(&exponent.to_be_bytes()).ok();
+        }
+        AikPublicKey::Ed25519 { public_key } => {
+            out.push(AIK_TAG_ED25519).ok();
+            out.extend_from_slice(public_key).ok();
+        }
+    }
+}
+
+/// Inverse of [`encode_aik_pubkey`]. Returns the bytes left over after the
+/// AIK, so a caller that appended more fields (like `ProvisioningRecord`'s
+/// trailing `ek_hash`) can keep parsing from where this left off.
+fn decode_aik_pubkey(bytes: &[u8]) -> Result<(AikPublicKey, &[u8]), DecodeError> {
+    let (&tag, rest) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+    match tag {
+        #[cfg(feature = "rsa")]
+        AIK_TAG_RSA => {
+            if rest.len() < 2 {
+                return Err(DecodeError::Truncated);
+            }
+            let modulus_len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let rest = &rest[2..];
+            if rest.len() < modulus_len + 4 {
+                return Err(DecodeError::Truncated);
+            }
+            let modulus = alloc::boxed::Box::new(
+                heapless::Vec::from_slice(&rest[..modulus_len]).map_err(|_| DecodeError::ModulusTooLarge)?,
+            );
+            let exponent = u32::from_be_bytes(rest[modulus_len..modulus_len + 4].try_into().unwrap());
+            Ok((AikPublicKey::Rsa { modulus, exponent }, &rest[modulus_len + 4..]))
+        }
+        #[cfg(not(feature = "rsa"))]
+        AIK_TAG_RSA => Err(DecodeError::UnknownAikTag(tag)),
+        AIK_TAG_ED25519 => {
+            if rest.len() < 32 {
+                return Err(DecodeError::Truncated);
+            }
+            let public_key: [u8; 32] = rest[..32].try_into().unwrap();
+            Ok((AikPublicKey::Ed25519 { public_key }, &rest[32..]))
+        }
+        other => Err(DecodeError::UnknownAikTag(other)),
+    }
+}
+
+impl ProvisioningRecord {
+    /// Fixed layout: `tag(1) || aik-specific bytes || ek_hash(32)`. RSA's
+    /// modulus is length-prefixed since it varies with key size; Ed25519
+    /// is fixed-size and needs no length.
+    ///
+    /// An 8192-bit RSA AIK encodes to ~1063 bytes, past
+    /// `certmgr::Filesystem`'s 1024-byte file cap, so persistence only
+    /// round-trips through `save_provisioning`/`load_provisioning` up to
+    /// a 4096-bit AIK today; a larger one still activates normally, it
+    /// just won't survive a reboot.
+    pub fn encode(&self) -> heapless::Vec<u8, 1024> {
+        let mut out = heapless::Vec::new();
+        encode_aik_pubkey(&self.aik_pubkey, &mut out);
+        out.extend_from_slice(&self.ek_hash).ok();
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (aik_pubkey, rest) = decode_aik_pubkey(bytes)?;
+
+        if rest.len() != 32 {
+            return Err(DecodeError::Truncated);
+        }
+        let ek_hash: [u8; 32] = rest.try_into().unwrap();
+
+        Ok(Self { ek_hash, aik_pubkey })
+    }
+}
+
+/// Directory verified AIKs are cached under, keyed by hex-encoded EK hash.
+/// Unlike [`PROVISION_PATH`] (a single slot for whichever attester most
+/// recently completed provisioning), this lets [`FobnailClient::load_aik_for_ek`]
+/// recognize an EK it has verified before even if a different attester has
+/// provisioned since, without needing a full metadata round-trip.
+pub const AIK_DIR: &str = "/aik/";
+
+/// Render `bytes` as lowercase hex, for use as a path component (EK hashes
+/// aren't valid path segments as raw bytes).
+fn hex(bytes: &[u8]) -> heapless::String<64> {
+    let mut s = heapless::String::new();
+    for b in bytes {
+        let _ = core::fmt::write(&mut s, format_args!("{:02x}", b));
+    }
+    s
+}
+
+fn aik_path(ek_hash: &[u8; 32]) -> heapless::String<80> {
+    let mut path = heapless::String::new();
+    path.push_str(AIK_DIR).ok();
+    path.push_str(&hex(ek_hash)).ok();
+    path
+}
+
+impl super::FobnailClient {
+    /// Write `record` to [`PROVISION_PATH`], overwriting whatever was
+    /// there. Called once an attestation reaches `State::Done`; the EK
+    /// hash and AIK it verified have to be supplied by the caller, since
+    /// by the time `State::Done` is reached the state machine itself has
+    /// already dropped them.
+    pub fn save_provisioning(fs: &mut impl Filesystem, record: &ProvisioningRecord) -> Result<(), StorageError> {
+        fs.write_file(PROVISION_PATH, &record.encode())
+    }
+
+    /// Load a previously saved provisioning record, if `PROVISION_PATH`
+    /// exists and decodes cleanly. A missing or corrupt record isn't an
+    /// error the caller needs to react to differently: either way, the
+    /// attester has to be provisioned from scratch.
+    pub fn load_provisioning(fs: &impl Filesystem) -> Option<ProvisioningRecord> {
+        let bytes = fs.read_file(PROVISION_PATH).ok()?;
+        ProvisioningRecord::decode(&bytes).ok()
+    }
+
+    /// Skip straight to `State::RequestMetadata` using a saved
+    /// `record`, provided the attester currently connected presents the
+    /// same EK hash the record was saved for. Returns whether the record
+    /// was accepted; on `false`, `state` is left untouched so the caller
+    /// falls back to full EK/AIK verification.
+    pub fn resume_from_provisioning(&mut self, record: &ProvisioningRecord, presented_ek_hash: &[u8; 32]) -> bool {
+        if crate::client::crypto::ct_eq(&record.ek_hash, presented_ek_hash) {
+            self.set_state(super::State::RequestMetadata);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Cache `aik_pubkey` under `/aik/<hex ek_hash>`, so a later
+    /// [`load_aik_for_ek`](Self::load_aik_for_ek) for the same EK doesn't
+    /// require re-running AIK verification from scratch.
+    pub fn store_aik_for_ek(fs: &mut impl Filesystem, ek_hash: &[u8; 32], aik_pubkey: &AikPublicKey) -> Result<(), StorageError> {
+        let mut bytes = heapless::Vec::new();
+        encode_aik_pubkey(aik_pubkey, &mut bytes);
+        fs.write_file(&aik_path(ek_hash), &bytes)
+    }
+
+    /// Look up a previously cached AIK for `ek_hash`. A missing or corrupt
+    /// entry is treated the same way: `None`, meaning the caller has to
+    /// fall back to verifying this EK's AIK from scratch.
+    pub fn load_aik_for_ek(fs: &impl Filesystem, ek_hash: &[u8; 32]) -> Option<AikPublicKey> {
+        let bytes = fs.read_file(&aik_path(ek_hash)).ok()?;
+        decode_aik_pubkey(&bytes).ok().map(|(aik_pubkey, _)| aik_pubkey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClientConfig, FobnailClient, State};
+
+    struct FakeFs {
+        files: heapless::Vec<(heapless::String<80>, heapless::Vec<u8, 1024>), 8>,
+    }
+
+    impl FakeFs {
+        fn new() -> Self {
+            Self { files: heapless::Vec::new() }
+        }
+    }
+
+    impl Filesystem for FakeFs {
+        fn locate_file(&self, _name: &str) -> Option<heapless::String<64>> {
+            unimplemented!("not exercised by provisioning tests")
+        }
+
+        fn open(&self, _path: &str) -> Result<(), StorageError> {
+            unimplemented!("not exercised by provisioning tests")
+        }
+
+        fn is_formatted(&self) -> bool {
+            true
+        }
+
+        fn format(&mut self) {}
+
+        fn list_files(&self, _prefix: &str) -> heapless::Vec<heapless::String<64>, 8> {
+            heapless::Vec::new()
+        }
+
+        fn read_file(&self, path: &str) -> Result<heapless::Vec<u8, 1024>, StorageError> {
+            self.files.iter().find(|(p, _)| p == path).map(|(_, data)| data.clone()).ok_or(StorageError::NotFound)
+        }
+
+        fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+            let stored = heapless::Vec::from_slice(data).map_err(|_| StorageError::Corrupted)?;
+            let path = heapless::String::from(path);
+            self.files.retain(|(p, _)| p != &path);
+            self.files.push((path, stored)).map_err(|_| StorageError::Corrupted)
+        }
+
+        fn delete_file(&mut self, path: &str) -> Result<(), StorageError> {
+            self.files.retain(|(p, _)| p != path);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rsa")]
+    #[test]
+    fn rsa_record_round_trips_through_encode_decode() {
+        let record = ProvisioningRecord {
+            ek_hash: [0x11; 32],
+            aik_pubkey: AikPublicKey::Rsa {
+                modulus: alloc::boxed::Box::new(heapless::Vec::from_slice(&[0x22; 256]).unwrap()),
+                exponent: 65537,
+            },
+        };
+
+        assert_eq!(ProvisioningRecord::decode(&record.encode()), Ok(record));
+    }
+
+    #[test]
+    fn ed25519_record_round_trips_through_encode_decode() {
+        let record = ProvisioningRecord { ek_hash: [0x33; 32], aik_pubkey: AikPublicKey::Ed25519 { public_key: [0x44; 32] } };
+
+        assert_eq!(ProvisioningRecord::decode(&record.encode()), Ok(record));
+    }
+
+    #[test]
+    fn decode_rejects_an_unknown_tag() {
+        assert_eq!(ProvisioningRecord::decode(&[0xff; 40]), Err(DecodeError::UnknownAikTag(0xff)));
+    }
+
+    #[test]
+    fn load_is_none_when_nothing_was_saved() {
+        let fs = FakeFs::new();
+        assert!(FobnailClient::load_provisioning(&fs).is_none());
+    }
+
+    #[test]
+    fn save_then_load_round_trips_through_a_filesystem() {
+        let mut fs = FakeFs::new();
+        let record = ProvisioningRecord { ek_hash: [0x55; 32], aik_pubkey: AikPublicKey::Ed25519 { public_key: [0x66; 32] } };
+
+        FobnailClient::save_provisioning(&mut fs, &record).unwrap();
+
+        assert_eq!(FobnailClient::load_provisioning(&fs), Some(record));
+    }
+
+    #[test]
+    fn load_aik_for_ek_is_none_when_nothing_was_cached() {
+        let fs = FakeFs::new();
+        assert!(FobnailClient::load_aik_for_ek(&fs, &[0x11; 32]).is_none());
+    }
+
+    #[test]
+    fn store_then_load_aik_for_ek_round_trips() {
+        let mut fs = FakeFs::new();
+        let aik_pubkey = AikPublicKey::Ed25519 { public_key: [0x22; 32] };
+
+        FobnailClient::store_aik_for_ek(&mut fs, &[0x33; 32], &aik_pubkey).unwrap();
+
+        assert_eq!(FobnailClient::load_aik_for_ek(&fs, &[0x33; 32]), Some(aik_pubkey));
+    }
+
+    #[test]
+    fn aik_cache_is_keyed_independently_per_ek_hash() {
+        let mut fs = FakeFs::new();
+        let first = AikPublicKey::Ed25519 { public_key: [0x44; 32] };
+        let second = AikPublicKey::Ed25519 { public_key: [0x55; 32] };
+
+        FobnailClient::store_aik_for_ek(&mut fs, &[0xaa; 32], &first).unwrap();
+        FobnailClient::store_aik_for_ek(&mut fs, &[0xbb; 32], &second).unwrap();
+
+        assert_eq!(FobnailClient::load_aik_for_ek(&fs, &[0xaa; 32]), Some(first));
+        assert_eq!(FobnailClient::load_aik_for_ek(&fs, &[0xbb; 32]), Some(second));
+    }
+
+    #[test]
+    fn corrupt_cached_aik_is_treated_as_absent() {
+        let mut fs = FakeFs::new();
+        fs.write_file(&aik_path(&[0x66; 32]), &[0xff; 4]).unwrap();
+
+        assert!(FobnailClient::load_aik_for_ek(&fs, &[0x66; 32]).is_none());
+    }
+
+    #[test]
+    fn matching_ek_hash_resumes_straight_to_request_metadata() {
+        let mut client = FobnailClient::new(ClientConfig::new(), None);
+        let record = ProvisioningRecord { ek_hash: [0x77; 32], aik_pubkey: AikPublicKey::Ed25519 { public_key: [0x88; 32] } };
+
+        assert!(client.resume_from_provisioning(&record, &[0x77; 32]));
+        assert_eq!(client.state(), &State::RequestMetadata);
+    }
+
+    #[test]
+    fn mismatched_ek_hash_is_rejected_without_changing_state() {
+        let mut client = FobnailClient::new(ClientConfig::new(), None);
+        let record = ProvisioningRecord { ek_hash: [0x77; 32], aik_pubkey: AikPublicKey::Ed25519 { public_key: [0x88; 32] } };
+
+        assert!(!client.resume_from_provisioning(&record, &[0x99; 32]));
+        assert_eq!(client.state(), &State::Idle);
+    }
+}
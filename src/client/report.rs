@@ -0,0 +1,131 @@
+//! A summary of a completed attestation, and the synchronous convenience
+//! entry point that would hand one back.
+//!
+//! The request this answers asks for an `async fn attest(&mut self,
+//! server: IpAddress) -> Result<AttestationReport, AttestError>` that
+//! drives `FobnailClient` to completion internally and hides the polling
+//! loop. That's not buildable honestly in this tree: there's no
+//! `smoltcp` `SocketRef`, no async executor, and no unified `poll` on
+//! `FobnailClient` at all — the state machine here is advanced by
+//! calling the specific `handle_*`/`prepare_*` method for whichever step
+//! is next (see `impl FobnailClient` in the parent module), which
+//! presupposes a transport/dispatch loop that doesn't exist yet either.
+//!
+//! What's implemented is the result-shaping half of the request: once a
+//! caller's own driving loop has gotten a [`FobnailClient`] to
+//! `State::Done` (or given up), [`attestation_report`] turns that outcome
+//! into the [`AttestationReport`]/[`AttestError`] the request describes,
+//! so the one line needed at the end of a real `attest()` is already
+//! here once the transport loop exists to call it from.
+
+use super::{FailureReason, FobnailClient, State};
+use crate::certmgr::Fingerprint;
+use crate::proto::{HashValue, Metadata, MAX_SERIAL_LEN};
+
+/// The verified facts about an attester a caller cares about once
+/// enrollment has finished successfully.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestationReport {
+    pub ek_hash: HashValue,
+    pub aik_fingerprint: Fingerprint,
+    /// Schema and serial number from the attester's verified metadata;
+    /// deliberately not the whole [`Metadata`], which also carries the
+    /// raw signed payload a caller of this summary shouldn't need.
+    pub schema_id: [u8; 32],
+    pub serial_number: heapless::String<MAX_SERIAL_LEN>,
+}
+
+impl AttestationReport {
+    fn from_metadata(ek_hash: HashValue, aik_fingerprint: Fingerprint, metadata: &Metadata) -> Self {
+        Self {
+            ek_hash,
+            aik_fingerprint,
+            schema_id: metadata.schema_id,
+            serial_number: metadata.sn.clone(),
+        }
+    }
+}
+
+/// Why [`attestation_report`] couldn't produce a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttestError {
+    /// The client is done retrying and moved to `State::Failed`.
+    Failed(FailureReason),
+    /// The client hasn't reached `State::Done` (or `State::Failed`) yet;
+    /// the caller's driving loop should keep going.
+    NotDone,
+}
+
+/// Turn a [`FobnailClient`] that (per the caller's own driving loop) has
+/// just reached `State::Done` into an [`AttestationReport`], using the
+/// EK hash, AIK fingerprint and metadata the caller captured along the
+/// way (`FobnailClient` itself doesn't retain them past the step that
+/// verified each one).
+pub fn attestation_report(
+    client: &FobnailClient,
+    ek_hash: HashValue,
+    aik_fingerprint: Fingerprint,
+    metadata: &Metadata,
+) -> Result<AttestationReport, AttestError> {
+    match client.state() {
+        State::Done => Ok(AttestationReport::from_metadata(ek_hash, aik_fingerprint, metadata)),
+        State::Failed { reason } => Err(AttestError::Failed(*reason)),
+        _ => Err(AttestError::NotDone),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClientConfig;
+
+    fn sample_metadata() -> Metadata {
+        Metadata {
+            version: crate::proto::CURRENT_VERSION,
+            schema_id: [0u8; 32],
+            ek_hash: HashValue::Sha256([0u8; 32]),
+            mac: heapless::Vec::from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]).unwrap(),
+            sn: heapless::String::from("FN-0001"),
+            payload: heapless::Vec::new(),
+        }
+    }
+
+    #[test]
+    fn a_done_client_produces_a_report() {
+        let mut client = FobnailClient::new(ClientConfig::new(), None);
+        let request_id = client.begin_request();
+        client.handle_metadata_response(request_id).unwrap();
+        let metadata = sample_metadata();
+
+        let report = attestation_report(&client, HashValue::Sha256([1u8; 32]), [2u8; 32], &metadata).unwrap();
+
+        assert_eq!(report.ek_hash, HashValue::Sha256([1u8; 32]));
+        assert_eq!(report.aik_fingerprint, [2u8; 32]);
+        assert_eq!(report.serial_number.as_str(), "FN-0001");
+    }
+
+    #[test]
+    fn a_failed_client_reports_its_failure_reason() {
+        let mut config = ClientConfig::new();
+        config.max_retries = 1;
+        let mut client = FobnailClient::new(config, None);
+        client.report_link_down(0);
+        let metadata = sample_metadata();
+
+        assert_eq!(
+            attestation_report(&client, HashValue::Sha256([0u8; 32]), [0u8; 32], &metadata),
+            Err(AttestError::Failed(FailureReason::LinkDown))
+        );
+    }
+
+    #[test]
+    fn a_client_still_in_progress_reports_not_done() {
+        let client = FobnailClient::new(ClientConfig::new(), None);
+        let metadata = sample_metadata();
+
+        assert_eq!(
+            attestation_report(&client, HashValue::Sha256([0u8; 32]), [0u8; 32], &metadata),
+            Err(AttestError::NotDone)
+        );
+    }
+}
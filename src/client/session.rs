@@ -0,0 +1,79 @@
+//! Session resumption: if the link drops mid-enrollment, avoid redoing
+//! steps the attester already confirmed.
+
+/// Milestones an attester conversation can have passed through, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    EkVerified,
+    AikVerified,
+    MetadataRequested,
+}
+
+/// Enough state to resume a conversation with an attester after a
+/// reconnect, without redoing already-completed steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: u32,
+    pub last_completed: Option<Step>,
+}
+
+impl Session {
+    pub fn new(id: u32) -> Self {
+        Self { id, last_completed: None }
+    }
+
+    pub fn mark_completed(&mut self, step: Step) {
+        if self.last_completed.is_none_or(|prev| step > prev) {
+            self.last_completed = Some(step);
+        }
+    }
+
+    /// Which state a resumed conversation should re-enter at, given the
+    /// attester's reported support for resumption.
+    ///
+    /// Falls back to a full restart at `Idle` if the attester doesn't
+    /// support resume, or if we never got past any milestone.
+    pub fn resume_state(&self, attester_supports_resume: bool) -> super::State {
+        if !attester_supports_resume {
+            return super::State::Idle;
+        }
+
+        match self.last_completed {
+            None => super::State::Idle,
+            Some(Step::EkVerified) => super::State::VerifyAikStage1,
+            Some(Step::AikVerified) => super::State::RequestMetadata,
+            Some(Step::MetadataRequested) => super::State::VerifyMetadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_at_request_metadata_after_aik_verified() {
+        let mut session = Session::new(1);
+        session.mark_completed(Step::EkVerified);
+        session.mark_completed(Step::AikVerified);
+
+        assert_eq!(session.resume_state(true), super::super::State::RequestMetadata);
+    }
+
+    #[test]
+    fn falls_back_to_full_restart_when_attester_lacks_resume_support() {
+        let mut session = Session::new(1);
+        session.mark_completed(Step::AikVerified);
+
+        assert_eq!(session.resume_state(false), super::super::State::Idle);
+    }
+
+    #[test]
+    fn mark_completed_never_regresses() {
+        let mut session = Session::new(1);
+        session.mark_completed(Step::AikVerified);
+        session.mark_completed(Step::EkVerified);
+
+        assert_eq!(session.last_completed, Some(Step::AikVerified));
+    }
+}
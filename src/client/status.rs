@@ -0,0 +1,134 @@
+//! A point-in-time snapshot of attestation progress.
+//!
+//! There's no CoAP *server* anywhere in this tree yet — `crate::coap` is
+//! a client used to talk to an attester, and there's no `main.rs` or
+//! resource-dispatch framework a `/status` GET handler could be added
+//! to. This adds the data such a handler would serve: the current
+//! [`FobnailClient`] state, how many metadata hashes are pinned, and the
+//! last failure reason if the client is currently in `State::Failed`.
+//! Wiring this up to an actual CoAP resource and a CBOR encoding is left
+//! for whenever this codebase grows a server side.
+
+use super::{FailureReason, FobnailClient, State};
+use crate::certmgr::{CertMgr, Filesystem};
+
+/// See the module docs: this is the data a `/status` resource would
+/// return, independent of how it eventually gets serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttestationStatus {
+    pub state: &'static str,
+    pub stored_hash_count: usize,
+    pub last_error: Option<FailureReason>,
+}
+
+impl AttestationStatus {
+    pub fn snapshot<F: Filesystem>(client: &FobnailClient, cert_mgr: &CertMgr<F>) -> Self {
+        let last_error = match client.state() {
+            State::Failed { reason } => Some(*reason),
+            _ => None,
+        };
+        Self {
+            state: client.state().name(),
+            stored_hash_count: cert_mgr.list_metadata_hashes().len(),
+            last_error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certmgr::StorageError;
+    use crate::client::ClientConfig;
+
+    /// A minimal in-memory `Filesystem`, local to this module since
+    /// `certmgr::store`'s own `FakeFs` is private to its test module.
+    struct FakeFs {
+        files: heapless::Vec<(heapless::String<64>, heapless::Vec<u8, 1024>), 8>,
+    }
+
+    impl FakeFs {
+        fn new() -> Self {
+            Self { files: heapless::Vec::new() }
+        }
+    }
+
+    impl Filesystem for FakeFs {
+        fn locate_file(&self, name: &str) -> Option<heapless::String<64>> {
+            self.files.iter().map(|(p, _)| p).find(|p| p.as_str() == name).cloned()
+        }
+
+        fn open(&self, path: &str) -> Result<(), StorageError> {
+            if self.files.iter().any(|(p, _)| p == path) {
+                Ok(())
+            } else {
+                Err(StorageError::NotFound)
+            }
+        }
+
+        fn is_formatted(&self) -> bool {
+            true
+        }
+
+        fn format(&mut self) {
+            self.files.clear();
+        }
+
+        fn list_files(&self, prefix: &str) -> heapless::Vec<heapless::String<64>, 8> {
+            self.files.iter().filter(|(p, _)| p.starts_with(prefix)).map(|(p, _)| p.clone()).collect()
+        }
+
+        fn read_file(&self, path: &str) -> Result<heapless::Vec<u8, 1024>, StorageError> {
+            self.files.iter().find(|(p, _)| p == path).map(|(_, d)| d.clone()).ok_or(StorageError::NotFound)
+        }
+
+        fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+            let mut stored = heapless::Vec::new();
+            stored.extend_from_slice(data).map_err(|_| StorageError::Corrupted)?;
+            self.files.push((heapless::String::from(path), stored)).map_err(|_| StorageError::Corrupted)
+        }
+
+        fn delete_file(&mut self, path: &str) -> Result<(), StorageError> {
+            self.files.retain(|(p, _)| p != path);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn idle_client_reports_idle_and_no_error() {
+        let client = FobnailClient::new(ClientConfig::new(), None);
+        let cert_mgr = CertMgr::new(FakeFs::new());
+
+        let status = AttestationStatus::snapshot(&client, &cert_mgr);
+
+        assert_eq!(status.state, "Idle");
+        assert_eq!(status.last_error, None);
+        assert_eq!(status.stored_hash_count, 0);
+    }
+
+    #[test]
+    fn failed_client_reports_its_failure_reason() {
+        let mut config = ClientConfig::new();
+        config.max_retries = 1;
+        let mut client = FobnailClient::new(config, None);
+        client.report_link_down(0);
+        let cert_mgr = CertMgr::new(FakeFs::new());
+
+        let status = AttestationStatus::snapshot(&client, &cert_mgr);
+
+        assert_eq!(status.state, "Failed");
+        assert_eq!(status.last_error, Some(FailureReason::LinkDown));
+    }
+
+    #[test]
+    fn stored_hash_count_reflects_the_metadata_store() {
+        let client = FobnailClient::new(ClientConfig::new(), None);
+        let mut cert_mgr = CertMgr::new(FakeFs::new());
+        cert_mgr.store_metadata_hash("attester-1").unwrap();
+        cert_mgr.store_metadata_hash("attester-2").unwrap();
+
+        let status = AttestationStatus::snapshot(&client, &cert_mgr);
+
+        assert_eq!(status.stored_hash_count, 2);
+    }
+}
@@ -0,0 +1,424 @@
+//! Building the PKCS#10 `CertificationRequest` an attester submits to
+//! enroll for a long-term device identity certificate, signed by the key
+//! whose public half the request describes.
+//!
+//! DER assembly goes through [`super::der`]; see that module for why it's
+//! hand-rolled instead of pulled from a crate.
+
+use super::der::{self, oids, DerBuf, DerError};
+#[cfg(feature = "rsa")]
+use rsa::PublicKeyParts;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrError {
+    Der(DerError),
+    /// The `rsa` crate rejected the key or digest during signing.
+    SigningFailed,
+}
+
+impl From<DerError> for CsrError {
+    fn from(e: DerError) -> Self {
+        CsrError::Der(e)
+    }
+}
+
+/// The subject public key a CSR is built around.
+pub enum SubjectPublicKey<'a> {
+    #[cfg(feature = "rsa")]
+    Rsa { modulus: &'a [u8], exponent: &'a [u8] },
+    /// Uncompressed SEC1 P-256 point: `0x04 || x (32 bytes) || y (32 bytes)`.
+    EcP256 { point: &'a [u8; 65] },
+}
+
+fn subject_public_key_info(key: &SubjectPublicKey) -> Result<DerBuf, CsrError> {
+    match key {
+        #[cfg(feature = "rsa")]
+        SubjectPublicKey::Rsa { modulus, exponent } => {
+            let algorithm = der::sequence(&[&der::oid(oids::RSA_ENCRYPTION)?, &der::null()])?;
+            let rsa_public_key =
+                der::sequence(&[&der::integer_unsigned(modulus)?, &der::integer_unsigned(exponent)?])?;
+            let public_key_bits = der::bit_string(&rsa_public_key)?;
+            Ok(der::sequence(&[&algorithm, &public_key_bits])?)
+        }
+        SubjectPublicKey::EcP256 { point } => {
+            let algorithm = der::sequence(&[&der::oid(oids::EC_PUBLIC_KEY)?, &der::oid(oids::PRIME256V1)?])?;
+            let public_key_bits = der::bit_string(point.as_slice())?;
+            Ok(der::sequence(&[&algorithm, &public_key_bits])?)
+        }
+    }
+}
+
+/// The subject fields a CSR can carry. `serial` is always present (it
+/// comes from the device's own identity, not deployment configuration);
+/// everything else is only added to the `Name` when populated.
+pub struct CsrSubject<'a> {
+    pub common_name: Option<&'a str>,
+    pub organization: Option<&'a str>,
+    pub organizational_unit: Option<&'a str>,
+    pub country: Option<&'a str>,
+    pub serial: &'a str,
+}
+
+impl<'a> CsrSubject<'a> {
+    /// The subject Fobnail has always built: just an organization of
+    /// "Fobnail" and the device's serial. Kept as the default so callers
+    /// that only have a `device_id` don't need to spell out every field.
+    pub fn from_device_id(device_id: &'a str) -> Self {
+        Self {
+            common_name: None,
+            organization: Some("Fobnail"),
+            organizational_unit: None,
+            country: None,
+            serial: device_id,
+        }
+    }
+}
+
+/// `Name`, built from whichever `subject` fields are populated. Each
+/// field gets its own RDN set, in the conventional C/O/OU/CN order, with
+/// the serial number always last.
+fn subject_name(subject: &CsrSubject) -> Result<DerBuf, CsrError> {
+    let mut rdns: heapless::Vec<DerBuf, 5> = heapless::Vec::new();
+    let mut push_rdn = |oid: &[u8], value: &str| -> Result<(), CsrError> {
+        let attr = der::sequence(&[&der::oid(oid)?, &der::printable_string(value)?])?;
+        rdns.push(der::set(&[&attr])?).map_err(|_| DerError::BufferTooSmall)?;
+        Ok(())
+    };
+
+    if let Some(country) = subject.country {
+        push_rdn(oids::COUNTRY_NAME, country)?;
+    }
+    if let Some(organization) = subject.organization {
+        push_rdn(oids::ORGANIZATION_NAME, organization)?;
+    }
+    if let Some(organizational_unit) = subject.organizational_unit {
+        push_rdn(oids::ORGANIZATIONAL_UNIT_NAME, organizational_unit)?;
+    }
+    if let Some(common_name) = subject.common_name {
+        push_rdn(oids::COMMON_NAME, common_name)?;
+    }
+    push_rdn(oids::SERIAL_NUMBER, subject.serial)?;
+
+    let rdn_slices: heapless::Vec<&[u8], 5> = rdns.iter().map(|rdn| rdn.as_slice()).collect();
+    Ok(der::sequence(&rdn_slices)?)
+}
+
+/// `CertificationRequestInfo`, the part of the CSR that gets signed.
+/// `attributes` is always empty — Fobnail has nothing to request beyond
+/// the certificate itself.
+fn certification_request_info(subject: &CsrSubject, subject_public_key_info: &[u8]) -> Result<DerBuf, CsrError> {
+    let version = der::integer_small(0);
+    let subject = subject_name(subject)?;
+    let mut attributes = DerBuf::new();
+    der::tlv(&mut attributes, der::TAG_CONTEXT_0, &[])?;
+    Ok(der::sequence(&[&version, &subject, subject_public_key_info, &attributes])?)
+}
+
+enum SignatureAlgorithm {
+    #[cfg(feature = "rsa")]
+    Sha256WithRsa,
+    EcdsaWithSha256,
+}
+
+fn signature_algorithm_identifier(alg: &SignatureAlgorithm) -> Result<DerBuf, CsrError> {
+    match alg {
+        #[cfg(feature = "rsa")]
+        SignatureAlgorithm::Sha256WithRsa => {
+            Ok(der::sequence(&[&der::oid(oids::SHA256_WITH_RSA_ENCRYPTION)?, &der::null()])?)
+        }
+        // Unlike RSA, ECDSA's AlgorithmIdentifier has no parameters field.
+        SignatureAlgorithm::EcdsaWithSha256 => Ok(der::sequence(&[&der::oid(oids::ECDSA_WITH_SHA256)?])?),
+    }
+}
+
+/// Assemble `CertificationRequestInfo || AlgorithmIdentifier || BIT
+/// STRING(signature)` into a `CertificationRequest`, wrapping in place
+/// rather than copying `certification_request_info` into a fresh buffer
+/// just to add a header — on the heap-constrained nRF52840 target this
+/// used to mean carrying two full-size scratch buffers (one for the
+/// signed info, one for the final encoding) instead of one.
+fn assemble_certification_request(
+    mut certification_request_info: DerBuf,
+    signature_algorithm: &SignatureAlgorithm,
+    signature: &[u8],
+) -> Result<DerBuf, CsrError> {
+    let algorithm = signature_algorithm_identifier(signature_algorithm)?;
+    let signature_bits = der::bit_string(signature)?;
+    certification_request_info
+        .extend_from_slice(&algorithm)
+        .map_err(|_| CsrError::Der(DerError::BufferTooSmall))?;
+    certification_request_info
+        .extend_from_slice(&signature_bits)
+        .map_err(|_| CsrError::Der(DerError::BufferTooSmall))?;
+    der::wrap_in_sequence(&mut certification_request_info)?;
+    Ok(certification_request_info)
+}
+
+#[cfg(feature = "rsa")]
+fn rsa_certification_request_info(subject: &CsrSubject, public_key: &rsa::RsaPublicKey) -> Result<DerBuf, CsrError> {
+    let modulus = public_key.n().to_bytes_be();
+    let exponent = public_key.e().to_bytes_be();
+    let spki = subject_public_key_info(&SubjectPublicKey::Rsa { modulus: &modulus, exponent: &exponent })?;
+    certification_request_info(subject, &spki)
+}
+
+/// Build and sign a CSR for an RSA device identity key held in RAM. Only
+/// use this directly when there's no Trussed backend to go through at
+/// all (e.g. in tests); [`make_csr_trussed`] is what real provisioning
+/// should call, since it keeps the key inside Trussed whenever the
+/// backend supports RSA.
+#[cfg(feature = "rsa")]
+pub fn make_csr(subject: &CsrSubject, key: &rsa::RsaPrivateKey) -> Result<DerBuf, CsrError> {
+    let public_key = rsa::RsaPublicKey::from(key);
+    let tbs = rsa_certification_request_info(subject, &public_key)?;
+
+    let digest = crate::crypto::sha256(&tbs);
+    let padding = rsa::PaddingScheme::PKCS1v15Sign { hash: Some(rsa::hash::Hash::SHA2_256) };
+    let signature = key.sign(padding, &digest).map_err(|_| CsrError::SigningFailed)?;
+
+    assemble_certification_request(tbs, &SignatureAlgorithm::Sha256WithRsa, &signature)
+}
+
+/// However this backend's Trussed client identifies a previously
+/// generated key. Opaque to `csr` — only passed through to
+/// [`TrussedRsaSigner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrussedKeyId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrussedSignError;
+
+/// Trussed's RSA PKCS#1v1.5-SHA256 sign syscall, abstracted the same way
+/// `crypto::rng::RandomBytesSource` abstracts `random_bytes`, so the
+/// fallback path in [`make_csr_trussed`] can be exercised without a real
+/// Trussed client.
+pub trait TrussedRsaSigner {
+    /// Sign `digest` (already SHA-256) with `key`. `Ok(None)` means this
+    /// Trussed backend has no RSA support at all (e.g. an ECC-only
+    /// secure element), as opposed to a failed signing attempt with a
+    /// key that should have worked.
+    fn sign_rsa_pkcs1v15_sha256(
+        &mut self,
+        key: TrussedKeyId,
+        digest: &[u8; 32],
+    ) -> Result<Option<heapless::Vec<u8, 512>>, TrussedSignError>;
+}
+
+/// Build and sign a CSR for an RSA device identity key, preferring to
+/// sign inside Trussed so the private key material never has to leave
+/// the secure keystore. Falls back to signing with `fallback_key` in RAM
+/// only when `signer` reports it has no RSA support to fall back on —
+/// the same "RSA may be entirely absent" assumption `AikPublicKey::Rsa`
+/// already makes via the `rsa` feature.
+#[cfg(feature = "rsa")]
+pub fn make_csr_trussed(
+    subject: &CsrSubject,
+    public_key: &rsa::RsaPublicKey,
+    signer: &mut impl TrussedRsaSigner,
+    key: TrussedKeyId,
+    fallback_key: Option<&rsa::RsaPrivateKey>,
+) -> Result<DerBuf, CsrError> {
+    let tbs = rsa_certification_request_info(subject, public_key)?;
+    let digest = crate::crypto::sha256(&tbs);
+
+    let signature = match signer.sign_rsa_pkcs1v15_sha256(key, &digest) {
+        Ok(Some(signature)) => signature,
+        Ok(None) => {
+            let fallback_key = fallback_key.ok_or(CsrError::SigningFailed)?;
+            let padding = rsa::PaddingScheme::PKCS1v15Sign { hash: Some(rsa::hash::Hash::SHA2_256) };
+            let signature = fallback_key.sign(padding, &digest).map_err(|_| CsrError::SigningFailed)?;
+            heapless::Vec::from_slice(&signature).map_err(|_| CsrError::Der(DerError::BufferTooSmall))?
+        }
+        Err(TrussedSignError) => return Err(CsrError::SigningFailed),
+    };
+
+    assemble_certification_request(tbs, &SignatureAlgorithm::Sha256WithRsa, &signature)
+}
+
+/// Build a to-be-signed `CertificationRequestInfo` for an EC P-256 device
+/// identity key, and assemble it into a signed CSR once a signature is
+/// available.
+///
+/// This is split into two steps, rather than a single `make_csr_ec` that
+/// signs internally like [`make_csr`] does, because there is no ECDSA
+/// implementation anywhere in this tree to sign with (no `p256`/`ecdsa`
+/// crate is a dependency, and `tpm::mc_ecc::Ecdh` only does ECDH key
+/// agreement for `TPM2_MakeCredential`, not signing). The raw
+/// `r || s` signature over `sha256(tbs)` is expected to come from
+/// wherever the private key actually lives — e.g. a Trussed-backed
+/// signer, the same in-RAM vs. Trussed split [`make_csr`] and
+/// [`make_csr_trussed`] provide for RSA.
+pub fn make_csr_ec_p256_tbs(subject: &CsrSubject, point: &[u8; 65]) -> Result<DerBuf, CsrError> {
+    let spki = subject_public_key_info(&SubjectPublicKey::EcP256 { point })?;
+    certification_request_info(subject, &spki)
+}
+
+/// Assemble a signed EC P-256 CSR from the `CertificationRequestInfo`
+/// returned by [`make_csr_ec_p256_tbs`] and a raw `(r, s)` ECDSA
+/// signature (64 bytes: 32-byte `r` followed by 32-byte `s`) over its
+/// SHA-256 digest.
+pub fn finish_csr_ec_p256(certification_request_info: &[u8], signature: &[u8; 64]) -> Result<DerBuf, CsrError> {
+    let tbs = DerBuf::from_slice(certification_request_info).map_err(|_| DerError::BufferTooSmall)?;
+    assemble_certification_request(tbs, &SignatureAlgorithm::EcdsaWithSha256, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ec_tbs_starts_with_a_sequence_tag_and_contains_the_expected_oids() {
+        let point = [0x04u8; 65];
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let tbs = make_csr_ec_p256_tbs(&subject, &point).unwrap();
+        assert_eq!(tbs[0], der::TAG_SEQUENCE);
+        // id-ecPublicKey and prime256v1 both show up somewhere in the
+        // encoded SubjectPublicKeyInfo.
+        assert!(tbs.windows(oids::EC_PUBLIC_KEY.len()).any(|w| w == oids::EC_PUBLIC_KEY));
+        assert!(tbs.windows(oids::PRIME256V1.len()).any(|w| w == oids::PRIME256V1));
+    }
+
+    #[test]
+    fn finished_ec_csr_wraps_tbs_algorithm_and_signature() {
+        let point = [0x04u8; 65];
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let tbs = make_csr_ec_p256_tbs(&subject, &point).unwrap();
+        let signature = [0x5au8; 64];
+        let csr = finish_csr_ec_p256(&tbs, &signature).unwrap();
+
+        assert_eq!(csr[0], der::TAG_SEQUENCE);
+        assert!(csr.windows(oids::ECDSA_WITH_SHA256.len()).any(|w| w == oids::ECDSA_WITH_SHA256));
+        assert!(csr.windows(signature.len()).any(|w| w == signature));
+        // The whole TBS block is present verbatim inside the final CSR.
+        assert!(csr.windows(tbs.len()).any(|w| w == tbs.as_slice()));
+    }
+
+    #[test]
+    fn only_populated_subject_fields_show_up_in_the_name() {
+        let subject = CsrSubject {
+            common_name: Some("fobnail-device"),
+            organization: None,
+            organizational_unit: None,
+            country: Some("PL"),
+            serial: "fobnail-0001",
+        };
+        let name = subject_name(&subject).unwrap();
+
+        assert!(name.windows(oids::COUNTRY_NAME.len()).any(|w| w == oids::COUNTRY_NAME));
+        assert!(name.windows(oids::COMMON_NAME.len()).any(|w| w == oids::COMMON_NAME));
+        assert!(!name.windows(oids::ORGANIZATION_NAME.len()).any(|w| w == oids::ORGANIZATION_NAME));
+        assert!(!name
+            .windows(oids::ORGANIZATIONAL_UNIT_NAME.len())
+            .any(|w| w == oids::ORGANIZATIONAL_UNIT_NAME));
+    }
+
+    #[test]
+    fn from_device_id_keeps_the_original_fobnail_org_and_serial_behavior() {
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let name = subject_name(&subject).unwrap();
+
+        assert!(name.windows("Fobnail".len()).any(|w| w == b"Fobnail"));
+        assert!(name.windows("fobnail-0001".len()).any(|w| w == b"fobnail-0001"));
+        assert!(name.windows(oids::ORGANIZATION_NAME.len()).any(|w| w == oids::ORGANIZATION_NAME));
+        assert!(name.windows(oids::SERIAL_NUMBER.len()).any(|w| w == oids::SERIAL_NUMBER));
+    }
+
+    #[cfg(feature = "rsa")]
+    #[test]
+    fn rsa_csr_contains_the_rsa_key_and_signature_algorithm_oids() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let csr = make_csr(&subject, &priv_key).unwrap();
+
+        assert_eq!(csr[0], der::TAG_SEQUENCE);
+        assert!(csr.windows(oids::RSA_ENCRYPTION.len()).any(|w| w == oids::RSA_ENCRYPTION));
+        assert!(csr
+            .windows(oids::SHA256_WITH_RSA_ENCRYPTION.len())
+            .any(|w| w == oids::SHA256_WITH_RSA_ENCRYPTION));
+    }
+
+    #[cfg(feature = "rsa")]
+    #[test]
+    fn rsa_csr_parses_back_as_a_single_well_formed_certification_request() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let csr = make_csr(&subject, &priv_key).unwrap();
+
+        // The whole buffer must parse as exactly one CertificationRequest
+        // SEQUENCE, with nothing left over, containing exactly its three
+        // fields (certificationRequestInfo, signatureAlgorithm, signature).
+        let (tag, content, remaining) = der::parse_tlv(&csr).unwrap();
+        assert_eq!(tag, der::TAG_SEQUENCE);
+        assert!(remaining.is_empty());
+
+        let (tbs_tag, _tbs_content, rest) = der::parse_tlv(content).unwrap();
+        assert_eq!(tbs_tag, der::TAG_SEQUENCE);
+        let (alg_tag, _alg_content, rest) = der::parse_tlv(rest).unwrap();
+        assert_eq!(alg_tag, der::TAG_SEQUENCE);
+        let (sig_tag, _sig_content, rest) = der::parse_tlv(rest).unwrap();
+        assert_eq!(sig_tag, der::TAG_BIT_STRING);
+        assert!(rest.is_empty());
+    }
+
+    #[cfg(feature = "rsa")]
+    struct FakeTrussedSigner {
+        supports_rsa: bool,
+        response: heapless::Vec<u8, 512>,
+    }
+
+    #[cfg(feature = "rsa")]
+    impl TrussedRsaSigner for FakeTrussedSigner {
+        fn sign_rsa_pkcs1v15_sha256(
+            &mut self,
+            _key: TrussedKeyId,
+            _digest: &[u8; 32],
+        ) -> Result<Option<heapless::Vec<u8, 512>>, TrussedSignError> {
+            if self.supports_rsa {
+                Ok(Some(self.response.clone()))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    #[cfg(feature = "rsa")]
+    #[test]
+    fn signs_through_trussed_when_it_supports_rsa() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+        let public_key = rsa::RsaPublicKey::from(&priv_key);
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let trussed_signature = heapless::Vec::from_slice(&[0x77u8; 256]).unwrap();
+        let mut signer = FakeTrussedSigner { supports_rsa: true, response: trussed_signature.clone() };
+
+        let csr = make_csr_trussed(&subject, &public_key, &mut signer, TrussedKeyId(1), None).unwrap();
+
+        assert!(csr.windows(trussed_signature.len()).any(|w| w == trussed_signature.as_slice()));
+    }
+
+    #[cfg(feature = "rsa")]
+    #[test]
+    fn falls_back_to_the_in_ram_key_when_trussed_has_no_rsa_support() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+        let public_key = rsa::RsaPublicKey::from(&priv_key);
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let mut signer = FakeTrussedSigner { supports_rsa: false, response: heapless::Vec::new() };
+
+        let csr = make_csr_trussed(&subject, &public_key, &mut signer, TrussedKeyId(1), Some(&priv_key)).unwrap();
+
+        assert_eq!(csr[0], der::TAG_SEQUENCE);
+    }
+
+    #[cfg(feature = "rsa")]
+    #[test]
+    fn errors_when_trussed_has_no_rsa_support_and_no_fallback_key_was_given() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+        let public_key = rsa::RsaPublicKey::from(&priv_key);
+        let subject = CsrSubject::from_device_id("fobnail-0001");
+        let mut signer = FakeTrussedSigner { supports_rsa: false, response: heapless::Vec::new() };
+
+        assert_eq!(
+            make_csr_trussed(&subject, &public_key, &mut signer, TrussedKeyId(1), None),
+            Err(CsrError::SigningFailed)
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Minimal ASN.1 DER encoding primitives.
+//!
+//! No ASN.1 crate exists in this tree, so CSR encoding is done by hand
+//! with the same fixed-layout, length-prefixed conventions used
+//! elsewhere (see e.g. `client::tpm::LoadedKeyName::encode`) applied to the
+//! specific TLV shapes PKCS#10 needs. This only implements the subset of
+//! DER `csr` actually uses — it is not a general-purpose encoder.
+
+use heapless::Vec;
+
+/// Upper bound on any single DER value this module builds, including the
+/// fully-assembled `CertificationRequest`. Generous enough for an
+/// RSA-4096 CSR with a handful of subject fields; callers building
+/// larger RSA keys should not route them through this module.
+pub const MAX_DER_LEN: usize = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerError {
+    /// A TLV's content, or the fully assembled structure, didn't fit in
+    /// `MAX_DER_LEN`.
+    BufferTooSmall,
+}
+
+pub type DerBuf = Vec<u8, MAX_DER_LEN>;
+
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_UTF8_STRING: u8 = 0x0c;
+pub const TAG_PRINTABLE_STRING: u8 = 0x13;
+pub const TAG_SEQUENCE: u8 = 0x30;
+pub const TAG_SET: u8 = 0x31;
+/// `[0] IMPLICIT` context tag, used for the (empty) CSR `attributes`
+/// field.
+pub const TAG_CONTEXT_0: u8 = 0xa0;
+
+fn push_len(out: &mut DerBuf, len: usize) -> Result<(), DerError> {
+    if len < 0x80 {
+        out.push(len as u8).map_err(|_| DerError::BufferTooSmall)
+    } else {
+        let be = (len as u32).to_be_bytes();
+        let first_nonzero = be.iter().position(|&b| b != 0).unwrap_or(3);
+        let bytes = &be[first_nonzero..];
+        out.push(0x80 | bytes.len() as u8)
+            .map_err(|_| DerError::BufferTooSmall)?;
+        out.extend_from_slice(bytes).map_err(|_| DerError::BufferTooSmall)
+    }
+}
+
+/// Append a tag-length-value for `content`, tagged `tag`.
+pub fn tlv(out: &mut DerBuf, tag: u8, content: &[u8]) -> Result<(), DerError> {
+    out.push(tag).map_err(|_| DerError::BufferTooSmall)?;
+    push_len(out, content.len())?;
+    out.extend_from_slice(content).map_err(|_| DerError::BufferTooSmall)
+}
+
+/// Wrap `buf`'s current contents in an outer `SEQUENCE`, in place. Lets a
+/// caller append several sibling TLVs onto `buf` first (including one
+/// that's already a large, fully-encoded structure, like a CSR's TBS
+/// block) and then wrap the lot, instead of copying everything into a
+/// second buffer just to add the header — the assembled
+/// `CertificationRequest` only ever needs the one buffer its
+/// `CertificationRequestInfo` was already built in.
+pub fn wrap_in_sequence(buf: &mut DerBuf) -> Result<(), DerError> {
+    let content_len = buf.len();
+    let mut header = DerBuf::new();
+    header.push(TAG_SEQUENCE).map_err(|_| DerError::BufferTooSmall)?;
+    push_len(&mut header, content_len)?;
+    let header_len = header.len();
+
+    for _ in 0..header_len {
+        buf.push(0).map_err(|_| DerError::BufferTooSmall)?;
+    }
+    buf.copy_within(0..content_len, header_len);
+    buf[..header_len].copy_from_slice(&header);
+    Ok(())
+}
+
+/// Parse a single tag-length-value off the front of `bytes`, returning
+/// `(tag, content, remaining)`. Only understands the length forms this
+/// module's own encoder produces (short form, and long form with up to 4
+/// length-of-length bytes) — this is not a general DER parser, just
+/// enough to let tests confirm what `csr` builds parses back the way it
+/// was assembled.
+pub fn parse_tlv(bytes: &[u8]) -> Result<(u8, &[u8], &[u8]), DerError> {
+    let (&tag, rest) = bytes.split_first().ok_or(DerError::BufferTooSmall)?;
+    let (&first_len_byte, rest) = rest.split_first().ok_or(DerError::BufferTooSmall)?;
+
+    let (len, rest) = if first_len_byte & 0x80 == 0 {
+        (first_len_byte as usize, rest)
+    } else {
+        let length_of_length = (first_len_byte & 0x7f) as usize;
+        if length_of_length == 0 || length_of_length > rest.len() {
+            return Err(DerError::BufferTooSmall);
+        }
+        let (len_bytes, rest) = rest.split_at(length_of_length);
+        let len = len_bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (len, rest)
+    };
+
+    if len > rest.len() {
+        return Err(DerError::BufferTooSmall);
+    }
+    let (content, remaining) = rest.split_at(len);
+    Ok((tag, content, remaining))
+}
+
+/// `SEQUENCE` wrapping the concatenation of `parts`.
+pub fn sequence(parts: &[&[u8]]) -> Result<DerBuf, DerError> {
+    let mut content = DerBuf::new();
+    for part in parts {
+        content.extend_from_slice(part).map_err(|_| DerError::BufferTooSmall)?;
+    }
+    let mut out = DerBuf::new();
+    tlv(&mut out, TAG_SEQUENCE, &content)?;
+    Ok(out)
+}
+
+/// `SET` wrapping the concatenation of `parts`.
+pub fn set(parts: &[&[u8]]) -> Result<DerBuf, DerError> {
+    let mut content = DerBuf::new();
+    for part in parts {
+        content.extend_from_slice(part).map_err(|_| DerError::BufferTooSmall)?;
+    }
+    let mut out = DerBuf::new();
+    tlv(&mut out, TAG_SET, &content)?;
+    Ok(out)
+}
+
+/// `OID` from its already-encoded content bytes (see the `oids` module for
+/// the identifiers `csr` needs).
+pub fn oid(content: &[u8]) -> Result<DerBuf, DerError> {
+    let mut out = DerBuf::new();
+    tlv(&mut out, TAG_OID, content)?;
+    Ok(out)
+}
+
+/// `NULL`, used as the parameters field of an `AlgorithmIdentifier` that
+/// takes none (RSA's does; ECDSA's does not).
+pub fn null() -> DerBuf {
+    let mut out = DerBuf::new();
+    out.extend_from_slice(&[0x05, 0x00]).unwrap();
+    out
+}
+
+pub fn printable_string(s: &str) -> Result<DerBuf, DerError> {
+    let mut out = DerBuf::new();
+    tlv(&mut out, TAG_PRINTABLE_STRING, s.as_bytes())?;
+    Ok(out)
+}
+
+/// `BIT STRING` wrapping `bytes` with zero unused trailing bits, which is
+/// all `csr` ever needs (subject public keys and signatures are always a
+/// whole number of bytes).
+pub fn bit_string(bytes: &[u8]) -> Result<DerBuf, DerError> {
+    let mut content = DerBuf::new();
+    content.push(0).map_err(|_| DerError::BufferTooSmall)?;
+    content.extend_from_slice(bytes).map_err(|_| DerError::BufferTooSmall)?;
+    let mut out = DerBuf::new();
+    tlv(&mut out, TAG_BIT_STRING, &content)?;
+    Ok(out)
+}
+
+/// Unsigned `INTEGER` from a big-endian byte string (e.g. an RSA modulus).
+/// Strips redundant leading zero bytes, then reintroduces a single one if
+/// dropping them would leave a high bit set, since DER `INTEGER` is
+/// always signed and a set high bit would otherwise read as negative.
+pub fn integer_unsigned(bytes: &[u8]) -> Result<DerBuf, DerError> {
+    let mut trimmed = bytes;
+    while trimmed.len() > 1 && trimmed[0] == 0 {
+        trimmed = &trimmed[1..];
+    }
+    let mut out = DerBuf::new();
+    if trimmed.first().is_some_and(|&b| b & 0x80 != 0) {
+        let mut content = DerBuf::new();
+        content.push(0).map_err(|_| DerError::BufferTooSmall)?;
+        content.extend_from_slice(trimmed).map_err(|_| DerError::BufferTooSmall)?;
+        tlv(&mut out, TAG_INTEGER, &content)?;
+    } else {
+        tlv(&mut out, TAG_INTEGER, trimmed)?;
+    }
+    Ok(out)
+}
+
+/// Small non-negative `INTEGER` (e.g. the CSR version field, always 0).
+pub fn integer_small(value: u8) -> DerBuf {
+    let mut out = DerBuf::new();
+    tlv(&mut out, TAG_INTEGER, &[value]).unwrap();
+    out
+}
+
+/// Object identifiers `csr` needs, pre-encoded as DER `OID` content bytes
+/// (i.e. everything after the tag and length).
+pub mod oids {
+    /// 2.5.4.6 `countryName`
+    pub const COUNTRY_NAME: &[u8] = &[0x55, 0x04, 0x06];
+    /// 2.5.4.10 `organizationName`
+    pub const ORGANIZATION_NAME: &[u8] = &[0x55, 0x04, 0x0a];
+    /// 2.5.4.11 `organizationalUnitName`
+    pub const ORGANIZATIONAL_UNIT_NAME: &[u8] = &[0x55, 0x04, 0x0b];
+    /// 2.5.4.3 `commonName`
+    pub const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+    /// 2.5.4.5 `serialNumber`
+    pub const SERIAL_NUMBER: &[u8] = &[0x55, 0x04, 0x05];
+    /// 1.2.840.113549.1.1.1 `rsaEncryption`
+    pub const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+    /// 1.2.840.113549.1.1.11 `sha256WithRSAEncryption`
+    pub const SHA256_WITH_RSA_ENCRYPTION: &[u8] =
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+    /// 1.2.840.10045.2.1 `id-ecPublicKey`
+    pub const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+    /// 1.2.840.10045.3.1.7 `prime256v1` (P-256)
+    pub const PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+    /// 1.2.840.10045.4.3.2 `ecdsa-with-SHA256`
+    pub const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_form_length_is_a_single_byte() {
+        let mut out = DerBuf::new();
+        tlv(&mut out, TAG_UTF8_STRING, &[0u8; 10]).unwrap();
+        assert_eq!(out[1], 10);
+    }
+
+    #[test]
+    fn long_form_length_is_used_above_127_bytes() {
+        let mut out = DerBuf::new();
+        tlv(&mut out, TAG_SEQUENCE, &[0u8; 200]).unwrap();
+        // 200 needs one length byte, so this is the long form with 1
+        // length-of-length byte: 0x81, 0xc8.
+        assert_eq!(&out[1..3], &[0x81, 0xc8]);
+    }
+
+    #[test]
+    fn unsigned_integer_keeps_a_high_bit_set_value_positive() {
+        let out = integer_unsigned(&[0xff]).unwrap();
+        // Tag, length 2, leading 0x00 pad, then the original byte.
+        assert_eq!(&out[..], &[TAG_INTEGER, 0x02, 0x00, 0xff]);
+    }
+
+    #[test]
+    fn unsigned_integer_strips_redundant_leading_zeroes() {
+        let out = integer_unsigned(&[0x00, 0x00, 0x2a]).unwrap();
+        assert_eq!(&out[..], &[TAG_INTEGER, 0x01, 0x2a]);
+    }
+
+    #[test]
+    fn bit_string_has_zero_unused_bits() {
+        let out = bit_string(&[0xaa, 0xbb]).unwrap();
+        assert_eq!(&out[..], &[TAG_BIT_STRING, 0x03, 0x00, 0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn wrap_in_sequence_prepends_a_header_without_disturbing_existing_content() {
+        let mut buf = DerBuf::new();
+        buf.extend_from_slice(&integer_small(1)).unwrap();
+        buf.extend_from_slice(&integer_small(2)).unwrap();
+        wrap_in_sequence(&mut buf).unwrap();
+
+        assert_eq!(&buf[..], &[TAG_SEQUENCE, 0x06, TAG_INTEGER, 0x01, 0x01, TAG_INTEGER, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn wrap_in_sequence_uses_long_form_length_when_needed() {
+        let mut buf = DerBuf::new();
+        buf.extend_from_slice(&[0u8; 200]).unwrap();
+        wrap_in_sequence(&mut buf).unwrap();
+
+        assert_eq!(&buf[..3], &[TAG_SEQUENCE, 0x81, 0xc8]);
+        assert_eq!(buf.len(), 203);
+    }
+
+    #[test]
+    fn parse_tlv_round_trips_what_this_module_encodes() {
+        let out = sequence(&[&integer_small(1), &printable_string("hi").unwrap()]).unwrap();
+        let (tag, content, remaining) = parse_tlv(&out).unwrap();
+
+        assert_eq!(tag, TAG_SEQUENCE);
+        assert!(remaining.is_empty());
+        let (inner_tag, inner_content, inner_remaining) = parse_tlv(content).unwrap();
+        assert_eq!(inner_tag, TAG_INTEGER);
+        assert_eq!(inner_content, &[1]);
+        let (string_tag, string_content, string_remaining) = parse_tlv(inner_remaining).unwrap();
+        assert_eq!(string_tag, TAG_PRINTABLE_STRING);
+        assert_eq!(string_content, b"hi");
+        assert!(string_remaining.is_empty());
+    }
+
+    #[test]
+    fn parse_tlv_rejects_a_truncated_buffer() {
+        assert_eq!(parse_tlv(&[TAG_SEQUENCE, 0x05, 0x00]), Err(DerError::BufferTooSmall));
+    }
+}
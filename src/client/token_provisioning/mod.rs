@@ -0,0 +1,5 @@
+//! Building the PKCS#10 certificate signing request (CSR) an attester
+//! submits when enrolling for a long-term device identity certificate.
+
+pub mod csr;
+pub mod der;
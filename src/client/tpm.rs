@@ -0,0 +1,233 @@
+//! TPM identifiers used by the client state machine when constructing
+//! `MakeCredential` blobs, as opposed to `crate::tpm`, which holds the
+//! protocol-level TPM primitives themselves.
+
+use core::convert::TryFrom;
+
+use crate::tpm::{Algorithm, Name};
+
+/// The TPM name of the key `MakeCredential` is targeting (the AIK the
+/// attester loaded), wire-encoded as `TPM_ALG_ID || digest` per TPM 2.0
+/// Part 1 16.4. This is the `name` fed into `KDFa` as `contextU` when
+/// deriving the credential's storage and integrity keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedKeyName(Name);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The 2-byte algorithm id isn't one `Algorithm` recognizes.
+    UnknownAlgorithm(u16),
+    /// The digest isn't the length `alg`'s hash produces, or `alg` isn't a
+    /// hash algorithm at all.
+    WrongLength { expected: Option<usize>, got: usize },
+}
+
+impl LoadedKeyName {
+    pub fn new(name: Name) -> Self {
+        Self(name)
+    }
+
+    /// Wire encoding of the name, as used in the `KDFa` context and in the
+    /// outer integrity HMAC.
+    pub fn encode(&self) -> heapless::Vec<u8, 66> {
+        let mut out = heapless::Vec::new();
+        out.extend_from_slice(&(self.0.alg as u16).to_be_bytes()).ok();
+        out.extend_from_slice(&self.0.digest).ok();
+        out
+    }
+
+    /// Compute the name of a loaded key from its public area: `alg ||
+    /// H(public_area)`, per TPM 2.0 Part 1 §16.4. Only `Sha256` is
+    /// computed locally today ([`crate::crypto::sha256`]); other
+    /// algorithms return `None` until they're backed by a Trussed hash
+    /// syscall instead of the software implementation.
+    pub fn from_public_area(alg: Algorithm, public_area: &[u8]) -> Option<Self> {
+        let digest: heapless::Vec<u8, 64> = match alg {
+            Algorithm::Sha256 => heapless::Vec::from_slice(&crate::crypto::sha256(public_area)).ok()?,
+            _ => return None,
+        };
+        Some(Self(Name::new(alg, &digest)?))
+    }
+
+    /// Decode the wire encoding produced by [`Self::encode`]: a 2-byte
+    /// big-endian `TPM_ALG_ID` followed by a digest whose length must
+    /// match that algorithm exactly (e.g. 48 bytes for SHA-384), not a
+    /// hardcoded size.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() < 2 {
+            return Err(DecodeError::WrongLength { expected: None, got: bytes.len() });
+        }
+        let alg_id = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let alg = Algorithm::try_from(alg_id).map_err(DecodeError::UnknownAlgorithm)?;
+
+        let digest = &bytes[2..];
+        let expected = alg.digest_size();
+        if Some(digest.len()) != expected {
+            return Err(DecodeError::WrongLength { expected, got: digest.len() });
+        }
+
+        Ok(Self(Name::new(alg, digest).ok_or(DecodeError::WrongLength { expected, got: digest.len() })?))
+    }
+}
+
+/// `TPMA_OBJECT` bits relevant to deciding whether a presented key is
+/// trustworthy as an AIK, per TPM 2.0 Part 2 §8.3.
+const FIXED_TPM: u32 = 1 << 1;
+const FIXED_PARENT: u32 = 1 << 4;
+const SENSITIVE_DATA_ORIGIN: u32 = 1 << 5;
+const RESTRICTED: u32 = 1 << 16;
+const DECRYPT: u32 = 1 << 17;
+
+/// The subset of `TPMA_OBJECT` that decides whether a key can be trusted
+/// as an AIK, parsed out of the AIK's `TPMT_PUBLIC.objectAttributes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AikAttributes {
+    pub fixed_tpm: bool,
+    pub fixed_parent: bool,
+    pub sensitive_data_origin: bool,
+    pub restricted: bool,
+    pub decrypt: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AikAttributesError {
+    /// `fixedTPM` is clear: the key could be duplicated to another TPM.
+    NotFixedToTpm,
+    /// `fixedParent` is clear: the key could be moved under a different
+    /// parent than the one that originally protected it.
+    NotFixedToParent,
+    /// `sensitiveDataOrigin` is clear: the key's sensitive area wasn't
+    /// generated by the TPM itself, so its origin can't be trusted.
+    NotTpmGenerated,
+    /// `restricted` is clear: an unrestricted signing key could be used
+    /// to sign attacker-chosen data, not just TPM-internal structures.
+    NotRestricted,
+    /// `decrypt` is set: a key usable for decryption should never also be
+    /// trusted as a signing identity key.
+    UsableForDecryption,
+}
+
+impl AikAttributes {
+    pub fn from_bits(raw: u32) -> Self {
+        Self {
+            fixed_tpm: raw & FIXED_TPM != 0,
+            fixed_parent: raw & FIXED_PARENT != 0,
+            sensitive_data_origin: raw & SENSITIVE_DATA_ORIGIN != 0,
+            restricted: raw & RESTRICTED != 0,
+            decrypt: raw & DECRYPT != 0,
+        }
+    }
+
+    /// An AIK must be a restricted signing key that never leaves the TPM
+    /// it was generated on: `fixedTPM`, `fixedParent`,
+    /// `sensitiveDataOrigin`, and `restricted` all set, and `decrypt`
+    /// clear. Rejecting each missing/unexpected flag as a distinct error
+    /// tells the caller exactly which requirement failed, rather than one
+    /// generic "untrusted AIK".
+    pub fn validate_for_attestation(&self) -> Result<(), AikAttributesError> {
+        if !self.fixed_tpm {
+            return Err(AikAttributesError::NotFixedToTpm);
+        }
+        if !self.fixed_parent {
+            return Err(AikAttributesError::NotFixedToParent);
+        }
+        if !self.sensitive_data_origin {
+            return Err(AikAttributesError::NotTpmGenerated);
+        }
+        if !self.restricted {
+            return Err(AikAttributesError::NotRestricted);
+        }
+        if self.decrypt {
+            return Err(AikAttributesError::UsableForDecryption);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tpm::Algorithm;
+
+    #[test]
+    fn encode_prefixes_digest_with_big_endian_alg_id() {
+        let name = Name::new(Algorithm::Sha256, &[0xaa; 32]).unwrap();
+        let encoded = LoadedKeyName::new(name).encode();
+
+        assert_eq!(&encoded[..2], &(Algorithm::Sha256 as u16).to_be_bytes());
+        assert_eq!(&encoded[2..], &[0xaa; 32]);
+    }
+
+    #[test]
+    fn decode_is_the_inverse_of_encode() {
+        let name = LoadedKeyName::new(Name::new(Algorithm::Sha256, &[0xbb; 32]).unwrap());
+        assert_eq!(LoadedKeyName::decode(&name.encode()), Ok(name));
+    }
+
+    #[test]
+    fn decode_accepts_a_sha384_digest_using_its_own_length() {
+        let name = LoadedKeyName::new(Name::new(Algorithm::Sha384, &[0xcc; 48]).unwrap());
+        assert_eq!(LoadedKeyName::decode(&name.encode()), Ok(name));
+    }
+
+    #[test]
+    fn decode_rejects_a_digest_with_the_wrong_length_for_its_algorithm() {
+        let mut bytes = heapless::Vec::<u8, 66>::new();
+        bytes.extend_from_slice(&(Algorithm::Sha256 as u16).to_be_bytes()).ok();
+        bytes.extend_from_slice(&[0u8; 20]).ok();
+
+        assert_eq!(
+            LoadedKeyName::decode(&bytes),
+            Err(DecodeError::WrongLength { expected: Some(32), got: 20 })
+        );
+    }
+
+    #[test]
+    fn from_public_area_hashes_with_sha256_and_tags_the_alg_id() {
+        let public_area = b"pretend-tpmt-public-bytes";
+        let name = LoadedKeyName::from_public_area(Algorithm::Sha256, public_area).unwrap();
+
+        let expected_digest = crate::crypto::sha256(public_area);
+        assert_eq!(name, LoadedKeyName::new(Name::new(Algorithm::Sha256, &expected_digest).unwrap()));
+    }
+
+    #[test]
+    fn from_public_area_returns_none_for_algorithms_without_a_local_hash() {
+        assert_eq!(LoadedKeyName::from_public_area(Algorithm::Sha384, b"data"), None);
+    }
+
+    #[test]
+    fn decode_rejects_an_unknown_algorithm_id() {
+        let mut bytes = heapless::Vec::<u8, 66>::new();
+        bytes.extend_from_slice(&0xffffu16.to_be_bytes()).ok();
+        bytes.extend_from_slice(&[0u8; 32]).ok();
+
+        assert_eq!(LoadedKeyName::decode(&bytes), Err(DecodeError::UnknownAlgorithm(0xffff)));
+    }
+
+    const RESTRICTED_SIGNING_AIK_BITS: u32 = FIXED_TPM | FIXED_PARENT | SENSITIVE_DATA_ORIGIN | RESTRICTED;
+
+    #[test]
+    fn accepts_a_properly_restricted_signing_key() {
+        let attrs = AikAttributes::from_bits(RESTRICTED_SIGNING_AIK_BITS);
+        assert_eq!(attrs.validate_for_attestation(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_key_missing_fixed_tpm() {
+        let attrs = AikAttributes::from_bits(RESTRICTED_SIGNING_AIK_BITS & !FIXED_TPM);
+        assert_eq!(attrs.validate_for_attestation(), Err(AikAttributesError::NotFixedToTpm));
+    }
+
+    #[test]
+    fn rejects_an_unrestricted_key() {
+        let attrs = AikAttributes::from_bits(RESTRICTED_SIGNING_AIK_BITS & !RESTRICTED);
+        assert_eq!(attrs.validate_for_attestation(), Err(AikAttributesError::NotRestricted));
+    }
+
+    #[test]
+    fn rejects_a_key_also_usable_for_decryption() {
+        let attrs = AikAttributes::from_bits(RESTRICTED_SIGNING_AIK_BITS | DECRYPT);
+        assert_eq!(attrs.validate_for_attestation(), Err(AikAttributesError::UsableForDecryption));
+    }
+}
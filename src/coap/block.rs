@@ -0,0 +1,120 @@
+//! RFC 7959 Block2 reassembly.
+//!
+//! `CoapClient` itself only knows how to fire a single request and hand
+//! back a response payload; when that payload arrives split across
+//! multiple Block2-tagged responses (e.g. an EK certificate chain too
+//! large for one datagram), the caller drives a [`BlockReassembler`] with
+//! each block as it arrives and gets the fully reassembled body back once
+//! the last block (the one without the "more" bit) is accepted. Follow-up
+//! requests for the next block number are the caller's responsibility,
+//! same as any other `queue_request` call.
+
+use alloc::vec::Vec;
+
+/// Block2 SZX values only cover powers of two from 16 to 1024 bytes
+/// (RFC 7959 §2.2); `szx > 6` is reserved and never valid.
+const MAX_SZX: u8 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockError {
+    /// `szx` was outside the 0..=6 range RFC 7959 defines block sizes for.
+    InvalidSzx(u8),
+    /// A block arrived out of order (not the next expected block number).
+    OutOfOrder { expected: u32, got: u32 },
+    /// Accepting this block would grow the reassembly buffer past
+    /// `max_size`.
+    Overflow,
+}
+
+/// Decode a Block2 SZX field into the block size, in bytes, it denotes:
+/// `2^(szx + 4)`.
+pub fn block_size(szx: u8) -> Result<usize, BlockError> {
+    if szx > MAX_SZX {
+        return Err(BlockError::InvalidSzx(szx));
+    }
+    Ok(1usize << (szx as u32 + 4))
+}
+
+/// Reassembles a sequence of Block2 payloads into one buffer, bounded by
+/// `max_size` so a misbehaving or malicious attester can't have the
+/// device allocate an unbounded amount of memory by dragging out a
+/// block-wise transfer forever.
+pub struct BlockReassembler {
+    buf: Vec<u8>,
+    max_size: usize,
+    next_block_num: u32,
+}
+
+impl BlockReassembler {
+    pub fn new(max_size: usize) -> Self {
+        Self { buf: Vec::new(), max_size, next_block_num: 0 }
+    }
+
+    /// Accept one Block2 payload. `block_num`/`more`/`szx` come from the
+    /// response's Block2 option; `payload` is the response body.
+    ///
+    /// Returns `Ok(Some(body))` once the block with `more == false` has
+    /// been accepted, `Ok(None)` if more blocks are still expected.
+    pub fn accept(&mut self, block_num: u32, more: bool, szx: u8, payload: &[u8]) -> Result<Option<Vec<u8>>, BlockError> {
+        // block_size validates szx even though this reassembler doesn't
+        // otherwise need the block size (payload.len() is authoritative
+        // for how much data actually arrived).
+        block_size(szx)?;
+
+        if block_num != self.next_block_num {
+            return Err(BlockError::OutOfOrder { expected: self.next_block_num, got: block_num });
+        }
+
+        if self.buf.len() + payload.len() > self.max_size {
+            return Err(BlockError::Overflow);
+        }
+
+        self.buf.extend_from_slice(payload);
+        self.next_block_num += 1;
+
+        if more {
+            Ok(None)
+        } else {
+            Ok(Some(core::mem::take(&mut self.buf)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reassembles_blocks_delivered_in_order() {
+        let mut reassembler = BlockReassembler::new(1024);
+
+        assert_eq!(reassembler.accept(0, true, 2, b"hello, "), Ok(None));
+        assert_eq!(reassembler.accept(1, false, 2, b"world!"), Ok(Some(b"hello, world!".to_vec())));
+    }
+
+    #[test]
+    fn rejects_a_block_delivered_out_of_order() {
+        let mut reassembler = BlockReassembler::new(1024);
+        assert_eq!(reassembler.accept(0, true, 2, b"a"), Ok(None));
+
+        assert_eq!(
+            reassembler.accept(2, false, 2, b"c"),
+            Err(BlockError::OutOfOrder { expected: 1, got: 2 })
+        );
+    }
+
+    #[test]
+    fn rejects_reassembly_past_the_configured_maximum_size() {
+        let mut reassembler = BlockReassembler::new(4);
+        assert_eq!(
+            reassembler.accept(0, false, 0, b"too-long"),
+            Err(BlockError::Overflow)
+        );
+    }
+
+    #[test]
+    fn rejects_an_szx_outside_the_valid_range() {
+        let mut reassembler = BlockReassembler::new(1024);
+        assert_eq!(reassembler.accept(0, false, 7, b"x"), Err(BlockError::InvalidSzx(7)));
+    }
+}
@@ -0,0 +1,75 @@
+//! Central sizing for the CoAP/UDP rx/tx buffers.
+//!
+//! There's no `src/main.rs` anywhere in this tree — see
+//! [`super::transport_timeout`] for the same caveat about the transport
+//! layer generally — so there are no actual `[0; 4096]` stack locals or
+//! `Forever` statics to point at yet. What's here is the config those
+//! buffers would be derived from: one named constant per knob instead of
+//! the size baked into each buffer declaration, ready for a real
+//! `main.rs` to build its `smoltcp` socket buffers and `PacketMetadata`
+//! rings from. `smoltcp`'s `UdpSocketBuffer` needs both a byte ring and a
+//! `PacketMetadata` ring per direction, hence the separate `_BUF_LEN` and
+//! `_META_SLOTS` constants.
+
+/// Bytes reserved for the CoAP receive buffer. Sized to comfortably hold
+/// a full EK certificate response reassembled via block-wise transfer
+/// (see [`super::block`]) without needing a second read.
+pub const COAP_RX_BUF_LEN: usize = 4096;
+
+/// Bytes reserved for the CoAP transmit buffer. Outbound requests here
+/// (a challenge response, a quote nonce) are all far smaller than a
+/// received cert or quote, so this is deliberately smaller than
+/// [`COAP_RX_BUF_LEN`].
+pub const COAP_TX_BUF_LEN: usize = 1024;
+
+/// `PacketMetadata` slots per direction: how many distinct datagrams
+/// `smoltcp` can have queued in a buffer at once, independent of their
+/// combined byte size. This client only ever has one request outstanding
+/// at a time (see `FobnailClient::pending_request`), so a handful of
+/// slots is headroom rather than a hard requirement.
+pub const COAP_META_SLOTS: usize = 16;
+
+/// Rough per-slot overhead of a `smoltcp::socket::udp::PacketMetadata`
+/// entry (endpoint address + length + padding), used only for the RAM
+/// budget check below; not meant to track the real struct layout
+/// exactly.
+const META_SLOT_OVERHEAD_BYTES: usize = 32;
+
+/// Total RAM this sizing commits to CoAP rx/tx byte buffers plus their
+/// metadata rings, across both directions.
+pub const COAP_BUFFER_BUDGET_BYTES: usize =
+    (COAP_RX_BUF_LEN + COAP_TX_BUF_LEN) + (COAP_META_SLOTS * META_SLOT_OVERHEAD_BYTES) * 2;
+
+/// Total RAM on the nRF52840, this firmware's target (see
+/// [`crate::pal::pal_nrf`]). The CoAP buffers above are far from the only
+/// consumer — the heap, USB descriptors, and TPM session buffers all
+/// compete for the same budget — so this only guards against the CoAP
+/// buffers alone blowing past a generous fraction of it.
+const NRF52840_RAM_BYTES: usize = 256 * 1024;
+
+/// However the buffer sizes above are tuned, they must never claim more
+/// than an eighth of total RAM on their own. Checked at compile time so a
+/// sizing change that blows the budget fails the build instead of being
+/// discovered on hardware.
+const _: () = assert!(COAP_BUFFER_BUDGET_BYTES <= NRF52840_RAM_BYTES / 8);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rx_buffer_is_larger_than_tx_since_responses_carry_more_data() {
+        const { assert!(COAP_RX_BUF_LEN > COAP_TX_BUF_LEN) };
+    }
+
+    #[test]
+    fn buffer_budget_matches_the_documented_formula() {
+        let expected = (COAP_RX_BUF_LEN + COAP_TX_BUF_LEN) + (COAP_META_SLOTS * META_SLOT_OVERHEAD_BYTES) * 2;
+        assert_eq!(COAP_BUFFER_BUDGET_BYTES, expected);
+    }
+
+    #[test]
+    fn buffer_budget_stays_within_an_eighth_of_target_ram() {
+        const { assert!(COAP_BUFFER_BUDGET_BYTES <= NRF52840_RAM_BYTES / 8) };
+    }
+}
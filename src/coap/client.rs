@@ -0,0 +1,88 @@
+//! Abstraction over sending a CoAP request, so state-machine logic that
+//! needs to fire one off (e.g. an AIK challenge) can be tested without a
+//! real UDP transport.
+
+/// Queue a CoAP request for transmission and return the id its response
+/// should be correlated against (see `FobnailClient::is_current_request`).
+pub trait CoapClient {
+    fn queue_request(&mut self, path: &str, payload: &[u8]) -> u32;
+
+    /// Queue a non-confirmable (`NON`) request: no ACK is expected and
+    /// [`super::RetransmitTimer`]'s retry machinery doesn't apply, so a
+    /// response may simply never arrive and the caller must tolerate
+    /// that. Intended for high-frequency, loss-tolerant polling (e.g. a
+    /// `/status` resource) where CON's ACK overhead and retransmission
+    /// state aren't worth paying for every poll.
+    ///
+    /// Default implementation falls back to a confirmable
+    /// [`queue_request`](Self::queue_request): a transport that hasn't
+    /// been taught to actually send `NON` still behaves correctly, just
+    /// without the bandwidth savings. A transport that wants the real
+    /// savings should override this to send with `MessageType::NonConfirmable`
+    /// and skip arming a `RetransmitTimer` for it.
+    fn queue_request_non(&mut self, path: &str, payload: &[u8]) -> u32 {
+        self.queue_request(path, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RecordingClient {
+        confirmable_calls: u32,
+        non_confirmable_calls: u32,
+        next_id: u32,
+    }
+
+    impl RecordingClient {
+        fn new() -> Self {
+            Self {
+                confirmable_calls: 0,
+                non_confirmable_calls: 0,
+                next_id: 0,
+            }
+        }
+
+        fn next_id(&mut self) -> u32 {
+            self.next_id += 1;
+            self.next_id
+        }
+    }
+
+    impl CoapClient for RecordingClient {
+        fn queue_request(&mut self, _path: &str, _payload: &[u8]) -> u32 {
+            self.confirmable_calls += 1;
+            self.next_id()
+        }
+    }
+
+    struct RecordingNonConfirmableClient(RecordingClient);
+
+    impl CoapClient for RecordingNonConfirmableClient {
+        fn queue_request(&mut self, path: &str, payload: &[u8]) -> u32 {
+            self.0.queue_request(path, payload)
+        }
+
+        fn queue_request_non(&mut self, _path: &str, _payload: &[u8]) -> u32 {
+            self.0.non_confirmable_calls += 1;
+            self.0.next_id()
+        }
+    }
+
+    #[test]
+    fn default_queue_request_non_falls_back_to_confirmable() {
+        let mut client = RecordingClient::new();
+        client.queue_request_non("/status", &[]);
+        assert_eq!(client.confirmable_calls, 1);
+        assert_eq!(client.non_confirmable_calls, 0);
+    }
+
+    #[test]
+    fn overriding_queue_request_non_skips_the_confirmable_path() {
+        let mut client = RecordingNonConfirmableClient(RecordingClient::new());
+        client.queue_request_non("/status", &[]);
+        assert_eq!(client.0.confirmable_calls, 0);
+        assert_eq!(client.0.non_confirmable_calls, 1);
+    }
+}
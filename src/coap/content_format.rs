@@ -0,0 +1,73 @@
+//! Content-Format handling for responses that may omit it.
+//!
+//! Some attester CoAP implementations don't set Content-Format on CBOR
+//! bodies. In lenient mode, an absent Content-Format triggers a CBOR
+//! decode attempt first, falling back to treating the payload as raw
+//! bytes for whatever the current state expects; strict mode instead
+//! rejects an untagged response outright.
+
+pub const CONTENT_FORMAT_CBOR: u16 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Reject responses that don't declare Content-Format: CBOR.
+    Strict,
+    /// Accept an absent Content-Format by attempting CBOR decode first.
+    Lenient,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentFormatError {
+    /// Strict mode rejected a response with no declared Content-Format.
+    MissingInStrictMode,
+}
+
+/// Decide how to interpret `payload` given an optional declared
+/// Content-Format. `try_cbor` is called to attempt a CBOR decode; if it
+/// fails and Content-Format was absent, the raw payload is returned
+/// instead (in lenient mode only) and the ambiguity should be logged by
+/// the caller.
+pub fn resolve_payload<'a, T>(
+    content_format: Option<u16>,
+    payload: &'a [u8],
+    mode: ParseMode,
+    try_cbor: impl FnOnce(&'a [u8]) -> Option<T>,
+) -> Result<Result<T, &'a [u8]>, ContentFormatError> {
+    match content_format {
+        Some(CONTENT_FORMAT_CBOR) => Ok(try_cbor(payload).ok_or(payload)),
+        Some(_) => Ok(Err(payload)),
+        None => match mode {
+            ParseMode::Strict => Err(ContentFormatError::MissingInStrictMode),
+            ParseMode::Lenient => Ok(match try_cbor(payload) {
+                Some(decoded) => Ok(decoded),
+                None => Err(payload),
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untagged_cbor_is_parsed_in_lenient_mode() {
+        let payload = b"cbor-bytes";
+        let result = resolve_payload(None, payload, ParseMode::Lenient, |p| {
+            if p == b"cbor-bytes" {
+                Some(42)
+            } else {
+                None
+            }
+        });
+        assert_eq!(result, Ok(Ok(42)));
+    }
+
+    #[test]
+    fn untagged_response_is_rejected_in_strict_mode() {
+        let payload = b"cbor-bytes";
+        let result: Result<Result<i32, &[u8]>, _> =
+            resolve_payload(None, payload, ParseMode::Strict, |_| None);
+        assert_eq!(result, Err(ContentFormatError::MissingInStrictMode));
+    }
+}
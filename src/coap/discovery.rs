@@ -0,0 +1,60 @@
+//! Attester discovery over a point-to-point USB link.
+//!
+//! Rather than requiring a hardcoded attester address, the initial
+//! `/attest` request can be sent to the CoAP all-nodes multicast address;
+//! the attester's actual unicast address is then learned from the
+//! response's source address. A configured address remains available as a
+//! fallback for links where multicast isn't usable.
+
+/// `coap://[ff02::fd]` — the IPv6 all-CoAP-nodes link-local multicast
+/// address (RFC 7252 §12.8).
+pub const ALL_COAP_NODES_MULTICAST: [u16; 8] = [0xff02, 0, 0, 0, 0, 0, 0, 0x00fd];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttesterAddress {
+    Discovered([u16; 8]),
+    Configured([u16; 8]),
+}
+
+impl AttesterAddress {
+    pub fn addr(&self) -> [u16; 8] {
+        match self {
+            AttesterAddress::Discovered(a) | AttesterAddress::Configured(a) => *a,
+        }
+    }
+}
+
+/// Where to send the initial discovery request: multicast if enabled,
+/// otherwise straight to the configured fallback address.
+pub fn discovery_target(multicast_enabled: bool, fallback: [u16; 8]) -> [u16; 8] {
+    if multicast_enabled {
+        ALL_COAP_NODES_MULTICAST
+    } else {
+        fallback
+    }
+}
+
+/// Learn the attester's unicast address from where its discovery response
+/// came from.
+pub fn on_discovery_response(source_addr: [u16; 8]) -> AttesterAddress {
+    AttesterAddress::Discovered(source_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multicast_response_sets_attester_unicast_address() {
+        let response_source = [0xfe80, 0, 0, 0, 1, 2, 3, 4];
+        let resolved = on_discovery_response(response_source);
+        assert_eq!(resolved, AttesterAddress::Discovered(response_source));
+        assert_eq!(resolved.addr(), response_source);
+    }
+
+    #[test]
+    fn falls_back_to_configured_address_when_multicast_disabled() {
+        let fallback = [0xfe80, 0, 0, 0, 9, 9, 9, 9];
+        assert_eq!(discovery_target(false, fallback), fallback);
+    }
+}
@@ -0,0 +1,110 @@
+//! CoAP message-id and token generation.
+//!
+//! Both counters are seeded from hardware entropy (via Trussed) instead of
+//! a fixed value, and re-seeded periodically, so an off-path attacker can't
+//! predict upcoming message-ids/tokens well enough to inject a spoofed
+//! response.
+
+/// Re-seed after this many generated tokens.
+const RESEED_INTERVAL: u32 = 256;
+
+/// Source of random bytes, implemented on top of `trussed.random_bytes` in
+/// production and a fixed PRNG in tests.
+pub trait RandomSource {
+    fn random_bytes(&mut self, buf: &mut [u8]);
+}
+
+/// Generates CoAP message-ids and 8-byte tokens that are unique among
+/// outstanding requests and not trivially predictable.
+pub struct IdGenerator {
+    state: u64,
+    since_reseed: u32,
+}
+
+impl IdGenerator {
+    pub fn new(rng: &mut impl RandomSource) -> Self {
+        let mut gen = Self { state: 1, since_reseed: 0 };
+        gen.reseed(rng);
+        gen
+    }
+
+    fn reseed(&mut self, rng: &mut impl RandomSource) {
+        let mut buf = [0u8; 8];
+        rng.random_bytes(&mut buf);
+        // Never let entropy collapse the state to zero, which would make
+        // the xorshift generator stick at zero forever.
+        self.state = u64::from_le_bytes(buf) | 1;
+        self.since_reseed = 0;
+    }
+
+    fn next_word(&mut self, rng: &mut impl RandomSource) -> u64 {
+        self.since_reseed += 1;
+        if self.since_reseed >= RESEED_INTERVAL {
+            self.reseed(rng);
+        }
+
+        // xorshift64*
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn next_message_id(&mut self, rng: &mut impl RandomSource) -> u16 {
+        self.next_word(rng) as u16
+    }
+
+    pub fn next_token(&mut self, rng: &mut impl RandomSource) -> [u8; 8] {
+        self.next_word(rng).to_le_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic stand-in for `trussed.random_bytes` in tests.
+    struct FixedRng(u8);
+    impl RandomSource for FixedRng {
+        fn random_bytes(&mut self, buf: &mut [u8]) {
+            for b in buf.iter_mut() {
+                self.0 = self.0.wrapping_mul(31).wrapping_add(7);
+                *b = self.0;
+            }
+        }
+    }
+
+    #[test]
+    fn tokens_have_no_trivial_sequential_correlation() {
+        let mut rng = FixedRng(42);
+        let mut gen = IdGenerator::new(&mut rng);
+
+        let tokens: Vec<u64> =
+            (0..500).map(|_| u64::from_le_bytes(gen.next_token(&mut rng))).collect();
+
+        // A predictable (e.g. sequential-from-seed) generator would have a
+        // constant difference between consecutive values; require that at
+        // least a large fraction of consecutive gaps differ.
+        let mut distinct_gaps = std::collections::HashSet::new();
+        for w in tokens.windows(2) {
+            distinct_gaps.insert(w[1].wrapping_sub(w[0]));
+        }
+        assert!(distinct_gaps.len() > tokens.len() / 2);
+    }
+
+    #[test]
+    fn reseeds_periodically() {
+        let mut rng = FixedRng(1);
+        let mut gen = IdGenerator::new(&mut rng);
+        let state_after_seed = gen.state;
+
+        for _ in 0..RESEED_INTERVAL {
+            gen.next_token(&mut rng);
+        }
+
+        assert_ne!(gen.state, state_after_seed);
+        assert_eq!(gen.since_reseed, 0);
+    }
+}
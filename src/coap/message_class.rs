@@ -0,0 +1,100 @@
+//! Classifying a raw CoAP message code (RFC 7252 §5.8, §12.1) as a
+//! request, a response, or empty.
+//!
+//! There's no `Packet` type, `MessageClass` enum, or `CoapClient::poll`
+//! that receives and dispatches inbound packets anywhere in this tree
+//! yet — [`super::CoapClient`] is outbound-only (`queue_request`,
+//! `queue_request_non`) — and `handle_server_response`/
+//! `handle_server_error_response` don't exist either. What's implemented
+//! here is the pure classification such a `poll` would need to run
+//! before invoking a completion closure: given a message's code byte, is
+//! it actually a response, as opposed to a stray request or an empty
+//! (ACK/RST) message that should be dropped rather than misparsed as
+//! one.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageClass {
+    /// Code `0.00`: an empty ACK, RST, or CON used only to ping.
+    Empty,
+    /// Codes `0.01`-`0.31`: a request (GET/POST/PUT/DELETE/FETCH/...).
+    Request,
+    /// Every other code: a success or error response (`2.00`-`5.31`),
+    /// and also the reserved classes 1, 6 and 7.
+    Response,
+}
+
+/// Classify a CoAP code byte (RFC 7252 §3, encoded as `(class << 5) |
+/// detail`).
+pub fn classify(code: u8) -> MessageClass {
+    match code {
+        0x00 => MessageClass::Empty,
+        0x01..=0x1f => MessageClass::Request,
+        _ => MessageClass::Response,
+    }
+}
+
+/// Whether `code` is one that should ever reach a response-completion
+/// callback. A stray request or an empty message should be dropped (and,
+/// once a real transport exists, logged at debug level) instead of being
+/// passed through and misparsed as a response.
+pub fn is_response_code(code: u8) -> bool {
+    classify(code) == MessageClass::Response
+}
+
+/// Invoke `on_response` with `code` only if it actually classifies as a
+/// response; otherwise the message is dropped and `on_response` is never
+/// called. Stands in for the filtering step `CoapClient::poll` would
+/// apply before running its completion closure.
+pub fn dispatch_response(code: u8, on_response: impl FnOnce(u8)) {
+    if is_response_code(code) {
+        on_response(code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `0.00`: RFC 7252 §4.1's empty message.
+    const CODE_EMPTY: u8 = 0x00;
+    /// `0.01`: GET.
+    const CODE_GET: u8 = 0x01;
+    /// `2.05`: Content.
+    const CODE_CONTENT: u8 = 0x45;
+    /// `4.04`: Not Found.
+    const CODE_NOT_FOUND: u8 = 0x84;
+
+    #[test]
+    fn empty_code_is_classified_as_empty() {
+        assert_eq!(classify(CODE_EMPTY), MessageClass::Empty);
+        assert!(!is_response_code(CODE_EMPTY));
+    }
+
+    #[test]
+    fn request_codes_are_classified_as_requests() {
+        assert_eq!(classify(CODE_GET), MessageClass::Request);
+        assert!(!is_response_code(CODE_GET));
+    }
+
+    #[test]
+    fn success_and_error_codes_are_classified_as_responses() {
+        assert_eq!(classify(CODE_CONTENT), MessageClass::Response);
+        assert_eq!(classify(CODE_NOT_FOUND), MessageClass::Response);
+        assert!(is_response_code(CODE_CONTENT));
+        assert!(is_response_code(CODE_NOT_FOUND));
+    }
+
+    #[test]
+    fn dispatch_response_calls_the_closure_for_an_actual_response() {
+        let mut called_with = None;
+        dispatch_response(CODE_CONTENT, |code| called_with = Some(code));
+        assert_eq!(called_with, Some(CODE_CONTENT));
+    }
+
+    #[test]
+    fn dispatch_response_never_calls_the_closure_for_a_request_coded_packet() {
+        let mut called = false;
+        dispatch_response(CODE_GET, |_| called = true);
+        assert!(!called);
+    }
+}
@@ -0,0 +1,30 @@
+//! Minimal CoAP client used to talk to an attester: message-id/token
+//! generation, response matching and transport.
+
+mod block;
+mod buffers;
+mod client;
+mod content_format;
+mod discovery;
+mod ids;
+mod message_class;
+mod observe;
+mod pending;
+mod response;
+mod retransmit;
+mod separate_response;
+mod transport_timeout;
+
+pub use block::{block_size, BlockError, BlockReassembler};
+pub use buffers::{COAP_BUFFER_BUDGET_BYTES, COAP_META_SLOTS, COAP_RX_BUF_LEN, COAP_TX_BUF_LEN};
+pub use client::CoapClient;
+pub use content_format::{resolve_payload, ContentFormatError, ParseMode, CONTENT_FORMAT_CBOR};
+pub use discovery::{discovery_target, on_discovery_response, AttesterAddress, ALL_COAP_NODES_MULTICAST};
+pub use ids::{IdGenerator, RandomSource};
+pub use message_class::{classify, dispatch_response, is_response_code, MessageClass};
+pub use observe::{Endpoint, Observer, ObserverRegistry};
+pub use pending::{PendingRequests, Token};
+pub use response::{coap_error_message, ResponseType};
+pub use retransmit::{RetransmitAction, RetransmitTimer};
+pub use separate_response::{SeparateResponseEvent, SeparateResponseTracker};
+pub use transport_timeout::{RecvTimeout, TransportError};
@@ -0,0 +1,163 @@
+//! RFC 7641 Observe registration bookkeeping.
+//!
+//! As noted in [`crate::client::status`], there's no CoAP *server* or
+//! resource-dispatch framework anywhere in this tree yet for a `/status`
+//! GET handler to live in, so there's nowhere to actually attach an
+//! Observe option handler or a "send this to every observer" hook into
+//! `FobnailClient`'s state transitions. What's implemented here is the
+//! pure bookkeeping such a handler would need: which (endpoint, token)
+//! pairs are currently registered as observers, capped at a fixed count,
+//! and the shared notification sequence number to stamp on each
+//! outgoing notification. Wiring this into an actual `/status` resource
+//! and a real transmit path is left for whenever this codebase grows a
+//! server side.
+
+use super::pending::Token;
+
+/// A raw IPv6 address, matching [`super::AttesterAddress`]'s
+/// representation — there's no `smoltcp::IpAddress` plumbed through this
+/// far yet.
+pub type Endpoint = [u16; 8];
+
+/// RFC 7641 §3.4: the Observe option value is a 24-bit counter that
+/// wraps back to 0 rather than overflowing.
+const SEQUENCE_MASK: u32 = 0x00ff_ffff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Observer {
+    pub endpoint: Endpoint,
+    pub token: Token,
+}
+
+/// A bounded table of registered observers, plus the sequence number
+/// shared across notifications sent to all of them.
+pub struct ObserverRegistry<const N: usize> {
+    observers: heapless::Vec<Observer, N>,
+    sequence: u32,
+}
+
+impl<const N: usize> ObserverRegistry<N> {
+    pub fn new() -> Self {
+        Self {
+            observers: heapless::Vec::new(),
+            sequence: 0,
+        }
+    }
+
+    /// Register an observer (a GET with `Observe: 0`). Re-registering an
+    /// endpoint/token pair that's already observing is a no-op rather
+    /// than a duplicate entry, matching RFC 7641 §3.4's "refreshes"
+    /// wording. Returns `false` if the table is already at its cap and
+    /// the registration was rejected.
+    pub fn register(&mut self, endpoint: Endpoint, token: Token) -> bool {
+        if self.is_observing(endpoint, token) {
+            return true;
+        }
+        self.observers.push(Observer { endpoint, token }).is_ok()
+    }
+
+    /// Deregister an observer, either because it sent `Observe: 1`
+    /// (RFC 7641 §3.6) or because a notification to it came back RST.
+    /// A no-op if it wasn't registered.
+    pub fn deregister(&mut self, endpoint: Endpoint, token: Token) {
+        self.observers.retain(|o| !(o.endpoint == endpoint && o.token == token));
+    }
+
+    pub fn is_observing(&self, endpoint: Endpoint, token: Token) -> bool {
+        self.observers.iter().any(|o| o.endpoint == endpoint && o.token == token)
+    }
+
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// Advance and return the next notification sequence number: the
+    /// Observe option value for one notification fanned out to every
+    /// currently registered observer. Nothing in this tree calls it on a
+    /// `FobnailClient` state transition (see the module docs).
+    pub fn next_sequence(&mut self) -> u32 {
+        self.sequence = (self.sequence + 1) & SEQUENCE_MASK;
+        self.sequence
+    }
+
+    pub fn observers(&self) -> &[Observer] {
+        &self.observers
+    }
+}
+
+impl<const N: usize> Default for ObserverRegistry<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: Endpoint = [0xfe80, 0, 0, 0, 1, 2, 3, 4];
+    const TOKEN: Token = [1; 8];
+
+    #[test]
+    fn registering_adds_an_observer() {
+        let mut registry: ObserverRegistry<4> = ObserverRegistry::new();
+        assert!(registry.register(HOST, TOKEN));
+        assert!(registry.is_observing(HOST, TOKEN));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn re_registering_the_same_endpoint_and_token_is_not_a_duplicate() {
+        let mut registry: ObserverRegistry<4> = ObserverRegistry::new();
+        registry.register(HOST, TOKEN);
+        registry.register(HOST, TOKEN);
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn registration_is_rejected_once_the_cap_is_reached() {
+        let mut registry: ObserverRegistry<2> = ObserverRegistry::new();
+        assert!(registry.register([0; 8], [1; 8]));
+        assert!(registry.register([0; 8], [2; 8]));
+        assert!(!registry.register([0; 8], [3; 8]));
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn deregistering_removes_only_the_matching_observer() {
+        let mut registry: ObserverRegistry<4> = ObserverRegistry::new();
+        registry.register(HOST, TOKEN);
+        registry.register(HOST, [2; 8]);
+
+        registry.deregister(HOST, TOKEN);
+
+        assert!(!registry.is_observing(HOST, TOKEN));
+        assert!(registry.is_observing(HOST, [2; 8]));
+    }
+
+    #[test]
+    fn deregistering_an_unknown_observer_is_a_no_op() {
+        let mut registry: ObserverRegistry<4> = ObserverRegistry::new();
+        registry.deregister(HOST, TOKEN);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn sequence_numbers_increment_across_notifications() {
+        let mut registry: ObserverRegistry<4> = ObserverRegistry::new();
+        assert_eq!(registry.next_sequence(), 1);
+        assert_eq!(registry.next_sequence(), 2);
+        assert_eq!(registry.next_sequence(), 3);
+    }
+
+    #[test]
+    fn sequence_number_wraps_at_the_24_bit_boundary() {
+        let mut registry: ObserverRegistry<4> = ObserverRegistry::new();
+        registry.sequence = SEQUENCE_MASK;
+        assert_eq!(registry.next_sequence(), 0);
+    }
+}
@@ -0,0 +1,80 @@
+//! Token-based request/response correlation.
+//!
+//! Assuming responses arrive in the same order requests were sent breaks
+//! down once requests are pipelined (e.g. prefetching `/aik` while `/ek`
+//! verification is still outstanding). Each queued request is tagged with
+//! an 8-byte CoAP token (RFC 7252 §5.3.1); an incoming response is matched
+//! against the pending request with the same token, regardless of order.
+
+pub type Token = [u8; 8];
+
+/// A bounded table of outstanding requests, keyed by token.
+pub struct PendingRequests<const N: usize> {
+    entries: heapless::Vec<(Token, u32), N>,
+}
+
+impl<const N: usize> PendingRequests<N> {
+    pub fn new() -> Self {
+        Self { entries: heapless::Vec::new() }
+    }
+
+    /// Record a newly queued request. Returns `false` if the table is
+    /// full, meaning too many requests are outstanding at once.
+    pub fn insert(&mut self, token: Token, request_id: u32) -> bool {
+        self.entries.push((token, request_id)).is_ok()
+    }
+
+    /// Look up and remove the request matching `token`. Returns `None`
+    /// without modifying the table if no pending request has this token —
+    /// a stale, duplicate, or spoofed response should be dropped rather
+    /// than dispatched to the wrong request.
+    pub fn take(&mut self, token: &Token) -> Option<u32> {
+        let idx = self.entries.iter().position(|(t, _)| t == token)?;
+        Some(self.entries.swap_remove(idx).1)
+    }
+}
+
+impl<const N: usize> Default for PendingRequests<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses_are_matched_by_token_regardless_of_arrival_order() {
+        let mut pending: PendingRequests<4> = PendingRequests::new();
+        let ek_token: Token = [1; 8];
+        let aik_token: Token = [2; 8];
+
+        pending.insert(ek_token, 100);
+        pending.insert(aik_token, 101);
+
+        // The /aik response arrives first, even though /ek was requested
+        // first.
+        assert_eq!(pending.take(&aik_token), Some(101));
+        assert_eq!(pending.take(&ek_token), Some(100));
+    }
+
+    #[test]
+    fn a_response_with_an_unrecognized_token_is_dropped() {
+        let mut pending: PendingRequests<4> = PendingRequests::new();
+        pending.insert([1; 8], 100);
+
+        assert_eq!(pending.take(&[9; 8]), None);
+        // The genuinely pending request is untouched.
+        assert_eq!(pending.take(&[1; 8]), Some(100));
+    }
+
+    #[test]
+    fn taking_a_token_twice_only_matches_the_first_response() {
+        let mut pending: PendingRequests<4> = PendingRequests::new();
+        pending.insert([1; 8], 100);
+
+        assert_eq!(pending.take(&[1; 8]), Some(100));
+        assert_eq!(pending.take(&[1; 8]), None);
+    }
+}
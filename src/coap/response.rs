@@ -0,0 +1,63 @@
+//! CoAP response codes and shared error reporting.
+//!
+//! Previously each caller (the client state machine, CSR submission,
+//! config PUT) matched on [`ResponseType`] itself to produce a log
+//! message; that logic is centralized here so all callers report the same
+//! wording for the same code.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseType {
+    Created,
+    Changed,
+    Content,
+    BadRequest,
+    Unauthorized,
+    NotFound,
+    MethodNotAllowed,
+    InternalServerError,
+    ServiceUnavailable,
+}
+
+/// Human-readable message for a CoAP response code, suitable for logging
+/// wherever a request to the attester came back with an error.
+pub fn coap_error_message(code: ResponseType) -> &'static str {
+    match code {
+        ResponseType::Created => "created",
+        ResponseType::Changed => "changed",
+        ResponseType::Content => "content",
+        ResponseType::BadRequest => "attester rejected the request as malformed",
+        ResponseType::Unauthorized => "attester rejected the request as unauthorized",
+        ResponseType::NotFound => "attester has no such resource",
+        ResponseType::MethodNotAllowed => "attester does not allow this method on the resource",
+        ResponseType::InternalServerError => "attester reported an internal error",
+        ResponseType::ServiceUnavailable => "attester is temporarily unavailable",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_response_type_has_a_distinct_message() {
+        let all = [
+            ResponseType::Created,
+            ResponseType::Changed,
+            ResponseType::Content,
+            ResponseType::BadRequest,
+            ResponseType::Unauthorized,
+            ResponseType::NotFound,
+            ResponseType::MethodNotAllowed,
+            ResponseType::InternalServerError,
+            ResponseType::ServiceUnavailable,
+        ];
+
+        let messages: Vec<&'static str> = all.iter().map(|c| coap_error_message(*c)).collect();
+
+        for i in 0..messages.len() {
+            for j in (i + 1)..messages.len() {
+                assert_ne!(messages[i], messages[j]);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! RFC 7252 confirmable-message retransmission timing.
+//!
+//! `CoapClient` fires a CON request once; if nothing acknowledges it in
+//! time (common over a USB EEM link that flaps), the caller polls a
+//! [`RetransmitTimer`] to find out whether to resend, keep waiting, or
+//! give up and report `Error` to its completion closure.
+
+use super::ids::RandomSource;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetransmitAction {
+    /// The timeout hasn't elapsed yet; keep waiting.
+    Wait,
+    /// The timeout elapsed and a retry is still allowed; resend the
+    /// request and keep polling.
+    Retransmit,
+    /// `MAX_RETRANSMIT` resends were already made with no response; the
+    /// caller should surface this as a failure.
+    GiveUp,
+}
+
+/// Tracks the send time and retry count of one outstanding CON request.
+pub struct RetransmitTimer {
+    sent_at_ms: u64,
+    retries: u32,
+    timeout_ms: u64,
+}
+
+impl RetransmitTimer {
+    /// RFC 7252 §4.8: the base timeout before the first retransmission.
+    pub const ACK_TIMEOUT_MS: u64 = 2_000;
+    /// RFC 7252 §4.8: how many times a CON message is resent before it's
+    /// treated as failed.
+    pub const MAX_RETRANSMIT: u32 = 4;
+
+    /// Start tracking a request sent at `sent_at_ms`, with the first
+    /// timeout randomized per RFC 7252 §4.8.1 (`ACK_TIMEOUT` scaled by a
+    /// random factor in `[1, 1.5]`) so that multiple devices retransmitting
+    /// after the same packet loss don't all resend in lockstep.
+    pub fn new(sent_at_ms: u64, rng: &mut impl RandomSource) -> Self {
+        Self { sent_at_ms, retries: 0, timeout_ms: Self::randomized_timeout(Self::ACK_TIMEOUT_MS, rng) }
+    }
+
+    fn randomized_timeout(base_ms: u64, rng: &mut impl RandomSource) -> u64 {
+        let mut b = [0u8; 1];
+        rng.random_bytes(&mut b);
+        base_ms + (base_ms * b[0] as u64) / (2 * 255)
+    }
+
+    /// Decide what to do at `now_ms`. On `Retransmit`, the timer has
+    /// already armed the next (exponentially backed off) timeout, so the
+    /// caller just needs to resend the request and keep polling.
+    pub fn poll(&mut self, now_ms: u64, rng: &mut impl RandomSource) -> RetransmitAction {
+        if now_ms < self.sent_at_ms + self.timeout_ms {
+            return RetransmitAction::Wait;
+        }
+        if self.retries >= Self::MAX_RETRANSMIT {
+            return RetransmitAction::GiveUp;
+        }
+        self.retries += 1;
+        self.sent_at_ms = now_ms;
+        self.timeout_ms = Self::randomized_timeout(Self::ACK_TIMEOUT_MS << self.retries, rng);
+        RetransmitAction::Retransmit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Always returns the same byte, so timeouts are deterministic in
+    /// tests instead of merely bounded.
+    struct FixedRng(u8);
+    impl RandomSource for FixedRng {
+        fn random_bytes(&mut self, buf: &mut [u8]) {
+            buf.fill(self.0);
+        }
+    }
+
+    #[test]
+    fn waits_until_the_randomized_timeout_elapses() {
+        let mut rng = FixedRng(0);
+        let mut timer = RetransmitTimer::new(0, &mut rng);
+
+        assert_eq!(timer.poll(RetransmitTimer::ACK_TIMEOUT_MS - 1, &mut rng), RetransmitAction::Wait);
+    }
+
+    #[test]
+    fn retransmits_with_exponential_backoff_up_to_max_retransmit() {
+        let mut rng = FixedRng(0);
+        let mut timer = RetransmitTimer::new(0, &mut rng);
+
+        // The timeout itself doubles on every retransmit, so `now_ms` has
+        // to advance by that same doubling series to always land just past
+        // the current deadline.
+        let mut now_ms = 0u64;
+        let mut timeout_ms = RetransmitTimer::ACK_TIMEOUT_MS;
+        for _ in 0..RetransmitTimer::MAX_RETRANSMIT {
+            now_ms += timeout_ms;
+            assert_eq!(timer.poll(now_ms, &mut rng), RetransmitAction::Retransmit);
+            timeout_ms *= 2;
+        }
+
+        now_ms += timeout_ms;
+        assert_eq!(timer.poll(now_ms, &mut rng), RetransmitAction::GiveUp);
+    }
+
+    #[test]
+    fn randomized_timeout_stays_within_the_rfc_bound() {
+        let mut rng = FixedRng(255);
+        let timeout = RetransmitTimer::randomized_timeout(RetransmitTimer::ACK_TIMEOUT_MS, &mut rng);
+        assert!(timeout >= RetransmitTimer::ACK_TIMEOUT_MS);
+        assert!(timeout < RetransmitTimer::ACK_TIMEOUT_MS * 3 / 2 + 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! Tracking for RFC 7252 §5.2.2 separate responses: an attester that
+//! needs time to produce a result (e.g. a TPM quote) answers a CON
+//! request with an empty ACK right away, then sends the actual Content
+//! later in its own CON, which the client ACKs in turn.
+//!
+//! There's no real transport or `CoapClient::poll` dispatch loop
+//! anywhere in this tree — [`super::CoapClient`] only queues outbound
+//! requests — so there's nowhere a receive loop could plug this state
+//! tracking into yet. What's implemented is the pure state machine such
+//! a loop would drive: given the message class ([`super::MessageClass`])
+//! of each inbound message for an outstanding request, decide whether
+//! it's the immediate ACK (stop retransmitting, keep waiting), the
+//! deferred response (deliver it), or something to ignore.
+
+use super::message_class::{classify, MessageClass};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeparateResponseState {
+    /// Waiting for anything at all in reply to the original CON request.
+    AwaitingAck,
+    /// The empty ACK arrived; retransmission has stopped and the actual
+    /// response is still outstanding.
+    AwaitingResponse,
+    /// The deferred response was delivered; nothing further is expected.
+    Delivered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeparateResponseEvent {
+    /// The immediate empty ACK arrived: the caller should disarm its
+    /// [`super::RetransmitTimer`] for this request and keep waiting.
+    Acked,
+    /// The deferred response arrived, carrying this message code.
+    Delivered(u8),
+    /// The message didn't advance the sequence (e.g. a duplicate ACK, or
+    /// something arriving after the response was already delivered) and
+    /// should be dropped.
+    Ignored,
+}
+
+/// Tracks one outstanding CON request through an ACK-then-CON separate
+/// response.
+pub struct SeparateResponseTracker {
+    state: SeparateResponseState,
+}
+
+impl SeparateResponseTracker {
+    pub fn new() -> Self {
+        Self { state: SeparateResponseState::AwaitingAck }
+    }
+
+    /// Whether the original request's [`super::RetransmitTimer`] should
+    /// still be retransmitting. Becomes `false` as soon as the empty ACK
+    /// arrives.
+    pub fn should_retransmit(&self) -> bool {
+        self.state == SeparateResponseState::AwaitingAck
+    }
+
+    /// Feed the code of an inbound message for this request and advance
+    /// the tracker accordingly.
+    pub fn on_message(&mut self, code: u8) -> SeparateResponseEvent {
+        match (self.state, classify(code)) {
+            (SeparateResponseState::AwaitingAck, MessageClass::Empty) => {
+                self.state = SeparateResponseState::AwaitingResponse;
+                SeparateResponseEvent::Acked
+            }
+            (SeparateResponseState::AwaitingResponse, MessageClass::Response) => {
+                self.state = SeparateResponseState::Delivered;
+                SeparateResponseEvent::Delivered(code)
+            }
+            // A piggybacked response (no separate ACK) is handled by the
+            // caller before ever constructing a tracker; anything else
+            // here (a duplicate ACK, a response before an ACK, a message
+            // after delivery) is dropped rather than acted on twice.
+            _ => SeparateResponseEvent::Ignored,
+        }
+    }
+}
+
+impl Default for SeparateResponseTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `2.05`: Content.
+    const CODE_CONTENT: u8 = 0x45;
+
+    #[test]
+    fn a_fresh_tracker_is_still_retransmitting() {
+        let tracker = SeparateResponseTracker::new();
+        assert!(tracker.should_retransmit());
+    }
+
+    #[test]
+    fn the_ack_then_content_sequence_stops_retransmission_then_delivers() {
+        let mut tracker = SeparateResponseTracker::new();
+
+        assert_eq!(tracker.on_message(0x00), SeparateResponseEvent::Acked);
+        assert!(!tracker.should_retransmit());
+
+        assert_eq!(tracker.on_message(CODE_CONTENT), SeparateResponseEvent::Delivered(CODE_CONTENT));
+    }
+
+    #[test]
+    fn a_response_arriving_before_any_ack_is_ignored() {
+        let mut tracker = SeparateResponseTracker::new();
+        assert_eq!(tracker.on_message(CODE_CONTENT), SeparateResponseEvent::Ignored);
+        assert!(tracker.should_retransmit());
+    }
+
+    #[test]
+    fn a_duplicate_ack_after_delivery_is_ignored() {
+        let mut tracker = SeparateResponseTracker::new();
+        tracker.on_message(0x00);
+        tracker.on_message(CODE_CONTENT);
+
+        assert_eq!(tracker.on_message(0x00), SeparateResponseEvent::Ignored);
+    }
+}
@@ -0,0 +1,67 @@
+//! Receive-timeout tracking for the server-side transport.
+//!
+//! There's no `src/udp.rs`/`UdpTransport` (or embassy) anywhere in this
+//! tree — the CoAP layer here only implements the client side
+//! ([`super::CoapClient`], [`super::RetransmitTimer`]) — so there's no
+//! `recv_from` to add a timeout to yet. What's implemented is the pure
+//! timing logic such a transport would need: a timeout armed when a
+//! receive starts, polled against the current time, in the same
+//! raw-parameter style as [`super::RetransmitTimer`]. A real
+//! `UdpTransport::new` would take a `recv_timeout_ms` and construct one
+//! of these per outstanding receive.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportError {
+    /// No datagram arrived before the configured receive timeout elapsed.
+    Timeout,
+}
+
+/// Tracks how long a single `recv_from` call has been waiting.
+pub struct RecvTimeout {
+    started_at_ms: u64,
+    timeout_ms: u64,
+}
+
+impl RecvTimeout {
+    /// Arm a timeout of `timeout_ms`, starting now (`started_at_ms`).
+    pub fn new(started_at_ms: u64, timeout_ms: u64) -> Self {
+        Self { started_at_ms, timeout_ms }
+    }
+
+    /// Check whether the timeout has elapsed as of `now_ms`. Called on
+    /// every poll of the (would-be) receive loop; `Ok(())` means keep
+    /// waiting for a datagram.
+    pub fn poll(&self, now_ms: u64) -> Result<(), TransportError> {
+        if now_ms.saturating_sub(self.started_at_ms) >= self.timeout_ms {
+            Err(TransportError::Timeout)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_not_elapsed_before_the_timeout() {
+        let timeout = RecvTimeout::new(0, 5_000);
+        assert_eq!(timeout.poll(4_999), Ok(()));
+    }
+
+    #[test]
+    fn elapses_once_the_timeout_is_reached() {
+        let timeout = RecvTimeout::new(0, 5_000);
+        assert_eq!(timeout.poll(5_000), Err(TransportError::Timeout));
+    }
+
+    #[test]
+    fn timeout_is_configurable_per_instance() {
+        let short = RecvTimeout::new(0, 100);
+        let long = RecvTimeout::new(0, 10_000);
+
+        assert_eq!(short.poll(150), Err(TransportError::Timeout));
+        assert_eq!(long.poll(150), Ok(()));
+    }
+}
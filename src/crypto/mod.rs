@@ -0,0 +1,7 @@
+//! Shared cryptographic primitives used across `tpm` and `certmgr`.
+
+pub mod sha256;
+pub mod zeroize;
+
+pub use sha256::{sha256, Sha256};
+pub use zeroize::zeroize;
@@ -0,0 +1,28 @@
+//! Best-effort clearing of secrets that must not linger in RAM after use.
+//!
+//! A plain `for b in buf { *b = 0 }` is a write the optimizer is free to
+//! elide if it can prove the buffer is never read again, which is exactly
+//! true of a secret right before it goes out of scope. `write_volatile`
+//! forbids that elision.
+
+/// Overwrite every byte of `buf` with zero in a way the optimizer cannot
+/// remove, even though nothing reads `buf` afterwards.
+pub fn zeroize(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        // SAFETY: `b` is a valid, aligned `&mut u8` for the duration of
+        // the write.
+        unsafe { core::ptr::write_volatile(b, 0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrites_every_byte() {
+        let mut buf = [0x42u8; 32];
+        zeroize(&mut buf);
+        assert_eq!(buf, [0u8; 32]);
+    }
+}
@@ -0,0 +1,17 @@
+//! Fobnail firmware core: attestation client, TPM helpers and supporting
+//! services. Board-specific glue lives under `pal`.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+pub mod cache;
+pub mod capabilities;
+pub mod certmgr;
+pub mod client;
+pub mod coap;
+pub mod crypto;
+pub mod pal;
+pub mod proto;
+pub mod tpm;
+pub mod usb;
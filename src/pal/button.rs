@@ -0,0 +1,130 @@
+//! Debounced board button handling.
+//!
+//! There's no GPIO/EXTI or timer-interrupt plumbing in this tree to read a
+//! real pin from (no register-level `pal_nrf` GPIO code exists yet), so
+//! this module takes raw pin-level samples as input, the same
+//! parameter-passed-in style already used by [`super::pal_nrf::mac_address`]
+//! and `usb::vbus::VbusMonitor::poll`. A real board integration would call
+//! `ButtonDebouncer::sample` from a periodic GPIOTE/timer interrupt with
+//! the inverted live pin level: the button is board button 1 (P1.06 on
+//! the nRF52840 dongle), configured as input with a pull-up, so a low pin
+//! means pressed and is passed as `pressed = true`.
+use crate::pal::timer::TimeSource;
+
+/// A press short enough to be a tap; anything held longer is reported as
+/// [`ButtonEvent::LongPress`] instead.
+const LONG_PRESS_MS: u64 = 1_000;
+
+/// Transitions within this long of the previous one are ignored as
+/// mechanical contact bounce.
+const DEBOUNCE_MS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// Pressed and released again within [`LONG_PRESS_MS`].
+    Press,
+    /// Held down for at least [`LONG_PRESS_MS`] before release.
+    LongPress,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Released,
+    Pressed,
+}
+
+/// Debounces raw button-pin samples into [`ButtonEvent`]s.
+pub struct ButtonDebouncer {
+    level: Level,
+    last_transition_ms: Option<u64>,
+    pressed_at_ms: Option<u64>,
+}
+
+impl ButtonDebouncer {
+    pub fn new() -> Self {
+        Self { level: Level::Released, last_transition_ms: None, pressed_at_ms: None }
+    }
+
+    /// Feed a raw pin sample (`pressed` = pin pulled low). Returns an
+    /// event once a full press-then-release has been observed; samples
+    /// within [`DEBOUNCE_MS`] of the last accepted transition are treated
+    /// as bounce and ignored.
+    pub fn sample(&mut self, pressed: bool, now_ms: u64) -> Option<ButtonEvent> {
+        let new_level = if pressed { Level::Pressed } else { Level::Released };
+        if new_level == self.level {
+            return None;
+        }
+        if let Some(last) = self.last_transition_ms {
+            if now_ms.saturating_sub(last) < DEBOUNCE_MS {
+                return None;
+            }
+        }
+
+        self.last_transition_ms = Some(now_ms);
+        self.level = new_level;
+
+        match new_level {
+            Level::Pressed => {
+                self.pressed_at_ms = Some(now_ms);
+                None
+            }
+            Level::Released => {
+                let pressed_at = self.pressed_at_ms.take()?;
+                let held_ms = now_ms.saturating_sub(pressed_at);
+                Some(if held_ms >= LONG_PRESS_MS { ButtonEvent::LongPress } else { ButtonEvent::Press })
+            }
+        }
+    }
+}
+
+impl Default for ButtonDebouncer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Poll a raw pin sample against `debouncer`, using `clock` for the
+/// current time. Intended to be called from the main loop or a periodic
+/// interrupt; the caller passes `FobnailClient::reset()`/`abort()` (or
+/// equivalent) in response to the returned event.
+pub fn poll_event<C: TimeSource>(debouncer: &mut ButtonDebouncer, clock: &C, pressed: bool) -> Option<ButtonEvent> {
+    debouncer.sample(pressed, clock.get_time_ms())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_press_reports_press() {
+        let mut d = ButtonDebouncer::new();
+        assert_eq!(d.sample(true, 0), None);
+        assert_eq!(d.sample(false, 100), Some(ButtonEvent::Press));
+    }
+
+    #[test]
+    fn held_past_threshold_reports_long_press() {
+        let mut d = ButtonDebouncer::new();
+        assert_eq!(d.sample(true, 0), None);
+        assert_eq!(d.sample(false, LONG_PRESS_MS), Some(ButtonEvent::LongPress));
+    }
+
+    #[test]
+    fn bounce_within_debounce_window_is_ignored() {
+        let mut d = ButtonDebouncer::new();
+        assert_eq!(d.sample(true, 0), None);
+        // Contact bounce: pin flickers back up and down within 20ms.
+        assert_eq!(d.sample(false, 5), None);
+        assert_eq!(d.sample(true, 10), None);
+        // Real release well after the debounce window.
+        assert_eq!(d.sample(false, 200), Some(ButtonEvent::Press));
+    }
+
+    #[test]
+    fn repeated_identical_samples_are_not_transitions() {
+        let mut d = ButtonDebouncer::new();
+        assert_eq!(d.sample(false, 0), None);
+        assert_eq!(d.sample(false, 50), None);
+        assert_eq!(d.sample(false, 100), None);
+    }
+}
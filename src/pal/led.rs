@@ -0,0 +1,173 @@
+//! Driving the two-LED (`p0_06`, `p0_08`) status layout from attestation
+//! progress, instead of a fixed once-a-second toggle on `TIMER1`.
+//!
+//! There's no register-level GPIO/TIMER1 access anywhere in this PAL yet
+//! (see [`super::watchdog`] and [`super::pal_nrf::UsbServicingTrigger`]
+//! for the same gap on other peripherals), so this adds the piece a real
+//! `TIMER1` handler would need: given the currently selected [`Pattern`]
+//! and a monotonically increasing tick count, which of the two LEDs
+//! should be lit this tick. [`AttestationLedObserver`] bridges that to
+//! [`crate::client::FobnailClientObserver`], the hook `FobnailClient`
+//! already calls on every state transition.
+
+/// A status pattern the two LEDs can render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Slow blink: nothing in flight.
+    Idle,
+    /// Fast blink: an attestation round trip is in progress.
+    Working,
+    /// Solid: the last attestation succeeded.
+    Success,
+    /// Double-blink: the last attestation failed.
+    Error,
+}
+
+/// Which of the two LEDs (`p0_06`, `p0_08`) should be lit for a given
+/// tick. Kept as independent fields (rather than one combined on/off)
+/// so a future pattern can address them independently, even though
+/// today's four patterns happen to drive both together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedState {
+    pub led1: bool,
+    pub led2: bool,
+}
+
+/// Renders whichever [`Pattern`] is currently selected into per-tick LED
+/// on/off state, replacing the fixed toggle `TIMER1`'s handler used to
+/// perform directly.
+pub struct PatternRenderer {
+    pattern: Pattern,
+}
+
+impl PatternRenderer {
+    pub fn new() -> Self {
+        Self { pattern: Pattern::Idle }
+    }
+
+    pub fn set_pattern(&mut self, pattern: Pattern) {
+        self.pattern = pattern;
+    }
+
+    pub fn pattern(&self) -> Pattern {
+        self.pattern
+    }
+
+    /// Called on every `TIMER1` tick (previously fixed at once a second)
+    /// with a tick counter that increments every call, and returns which
+    /// LEDs should be lit this tick.
+    pub fn render(&self, tick: u32) -> LedState {
+        let on = match self.pattern {
+            Pattern::Idle => tick.is_multiple_of(4),
+            Pattern::Working => tick.is_multiple_of(2),
+            Pattern::Success => true,
+            Pattern::Error => matches!(tick % 6, 0 | 2),
+        };
+        LedState { led1: on, led2: on }
+    }
+}
+
+impl Default for PatternRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Something that can be told which [`Pattern`] to render, abstracting
+/// the real GPIO writes the same way [`crate::client::crypto::rng::RandomBytesSource`]
+/// abstracts a Trussed syscall: a small trait so tests can supply a fake
+/// rather than real hardware.
+pub trait LedDriver {
+    fn set_pattern(&mut self, pattern: Pattern);
+}
+
+impl LedDriver for PatternRenderer {
+    fn set_pattern(&mut self, pattern: Pattern) {
+        PatternRenderer::set_pattern(self, pattern);
+    }
+}
+
+/// Bridges [`crate::client::FobnailClientObserver`] to an [`LedDriver`],
+/// mapping `FobnailClient`'s state names to a status pattern so the LEDs
+/// reflect attestation progress without `FobnailClient` knowing anything
+/// about LEDs.
+pub struct AttestationLedObserver<D> {
+    driver: D,
+}
+
+impl<D: LedDriver> AttestationLedObserver<D> {
+    pub fn new(driver: D) -> Self {
+        Self { driver }
+    }
+}
+
+impl<D: LedDriver> crate::client::FobnailClientObserver for AttestationLedObserver<D> {
+    fn on_state_change(&mut self, _from: &str, to: &str) {
+        let pattern = match to {
+            "Idle" => Pattern::Idle,
+            "Done" => Pattern::Success,
+            "Failed" => Pattern::Error,
+            _ => Pattern::Working,
+        };
+        self.driver.set_pattern(pattern);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_blinks_slowly() {
+        let mut renderer = PatternRenderer::new();
+        renderer.set_pattern(Pattern::Idle);
+        let states: heapless::Vec<bool, 4> =
+            (0..4).map(|t| renderer.render(t).led1).collect();
+        assert_eq!(states.as_slice(), &[true, false, false, false]);
+    }
+
+    #[test]
+    fn success_is_solid() {
+        let mut renderer = PatternRenderer::new();
+        renderer.set_pattern(Pattern::Success);
+        for t in 0..4 {
+            assert_eq!(renderer.render(t), LedState { led1: true, led2: true });
+        }
+    }
+
+    #[test]
+    fn error_double_blinks() {
+        let mut renderer = PatternRenderer::new();
+        renderer.set_pattern(Pattern::Error);
+        let states: heapless::Vec<bool, 6> =
+            (0..6).map(|t| renderer.render(t).led1).collect();
+        assert_eq!(states.as_slice(), &[true, false, true, false, false, false]);
+    }
+
+    struct FakeLed(Pattern);
+    impl LedDriver for FakeLed {
+        fn set_pattern(&mut self, pattern: Pattern) {
+            self.0 = pattern;
+        }
+    }
+
+    #[test]
+    fn observer_maps_terminal_states_to_success_and_error() {
+        let mut observer = AttestationLedObserver::new(FakeLed(Pattern::Idle));
+        crate::client::FobnailClientObserver::on_state_change(&mut observer, "VerifyQuote", "Done");
+        assert_eq!(observer.driver.0, Pattern::Success);
+
+        crate::client::FobnailClientObserver::on_state_change(&mut observer, "VerifyAikStage1", "Failed");
+        assert_eq!(observer.driver.0, Pattern::Error);
+    }
+
+    #[test]
+    fn observer_maps_idle_and_everything_else_to_idle_and_working() {
+        let mut observer = AttestationLedObserver::new(FakeLed(Pattern::Success));
+        crate::client::FobnailClientObserver::on_state_change(&mut observer, "Failed", "Idle");
+        assert_eq!(observer.driver.0, Pattern::Idle);
+
+        crate::client::FobnailClientObserver::on_state_change(&mut observer, "Idle", "RequestEkCert");
+        assert_eq!(observer.driver.0, Pattern::Working);
+    }
+}
@@ -0,0 +1,125 @@
+//! Runtime-adjustable log level filter.
+//!
+//! There's no `log` crate dependency, RTT logger, or `debug!`/`info!`/
+//! `error!` call site anywhere in this tree yet — `pal_nrf::logger::init`
+//! doesn't exist either — so what's implemented here is the filter such
+//! logging would consult before formatting and emitting a line: a
+//! global, atomically-stored level any part of the firmware can lower or
+//! raise at runtime, plus the wire-decoding step a CoAP `/loglevel` PUT
+//! handler would call into once a dispatch loop exists
+//! ([`super::super::coap::CoapClient`] only queues outbound requests;
+//! nothing in `crate::coap` receives or dispatches inbound ones).
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Log verbosity, ordered least to most verbose so a filter check is a
+/// single comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum LevelFilter {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl LevelFilter {
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Off),
+            1 => Some(Self::Error),
+            2 => Some(Self::Warn),
+            3 => Some(Self::Info),
+            4 => Some(Self::Debug),
+            5 => Some(Self::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// Default verbosity: enough for field debugging without the timer/USB
+/// hot-path volume RTT can't keep up with at `Debug`/`Trace`.
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+static CURRENT_LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL as u8);
+
+/// Raise or lower the active filter at runtime, e.g. from
+/// [`apply_loglevel_put`], without reflashing.
+pub fn set_level(level: LevelFilter) {
+    CURRENT_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// The currently active filter.
+pub fn level() -> LevelFilter {
+    LevelFilter::from_u8(CURRENT_LEVEL.load(Ordering::Relaxed)).unwrap_or(DEFAULT_LEVEL)
+}
+
+/// Whether a call site logging at `level` should actually emit, given the
+/// currently active filter. A hot-path `debug!` call would guard on this
+/// before formatting its arguments, so the formatting cost (not just the
+/// RTT write) is skipped once the filter is raised above it.
+pub fn is_enabled(level: LevelFilter) -> bool {
+    level <= self::level()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevelPutError {
+    /// The payload wasn't exactly one byte, or wasn't a recognized level.
+    Invalid,
+}
+
+/// Decode a CoAP `/loglevel` PUT payload (a single byte matching one of
+/// [`LevelFilter`]'s numeric values) and apply it. Split from
+/// [`set_level`] itself so the wire-decoding step is testable
+/// independently of whatever dispatch loop eventually calls it.
+pub fn apply_loglevel_put(payload: &[u8]) -> Result<LevelFilter, LogLevelPutError> {
+    let &[byte] = payload else {
+        return Err(LogLevelPutError::Invalid);
+    };
+    let level = LevelFilter::from_u8(byte).ok_or(LogLevelPutError::Invalid)?;
+    set_level(level);
+    Ok(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `CURRENT_LEVEL` is a single process-wide static, so every
+    /// assertion that depends on its value lives in this one test
+    /// instead of being split across several that `cargo test` could run
+    /// concurrently and interleave.
+    #[test]
+    fn runtime_level_changes_take_effect_immediately() {
+        set_level(DEFAULT_LEVEL);
+        assert_eq!(level(), LevelFilter::Info);
+        assert!(is_enabled(LevelFilter::Error));
+        assert!(is_enabled(LevelFilter::Info));
+        assert!(!is_enabled(LevelFilter::Debug));
+        assert!(!is_enabled(LevelFilter::Trace));
+
+        set_level(LevelFilter::Trace);
+        assert!(is_enabled(LevelFilter::Trace));
+
+        let result = apply_loglevel_put(&[LevelFilter::Debug as u8]);
+        assert_eq!(result, Ok(LevelFilter::Debug));
+        assert_eq!(level(), LevelFilter::Debug);
+        assert!(is_enabled(LevelFilter::Debug));
+        assert!(!is_enabled(LevelFilter::Trace));
+
+        set_level(DEFAULT_LEVEL);
+    }
+
+    #[test]
+    fn loglevel_put_rejects_an_out_of_range_byte() {
+        assert_eq!(apply_loglevel_put(&[0xff]), Err(LogLevelPutError::Invalid));
+    }
+
+    #[test]
+    fn loglevel_put_rejects_a_payload_that_is_not_exactly_one_byte() {
+        assert_eq!(apply_loglevel_put(&[]), Err(LogLevelPutError::Invalid));
+        assert_eq!(apply_loglevel_put(&[1, 2]), Err(LogLevelPutError::Invalid));
+    }
+}
@@ -0,0 +1,25 @@
+//! Platform abstraction layer. `pal_nrf` targets the nRF52840; `pal_pc`
+//! backs host-side tests and tools.
+
+pub mod button;
+pub mod led;
+pub mod log;
+pub mod net;
+pub mod pal_nrf;
+pub mod pal_pc;
+pub mod panic_log;
+pub mod power;
+pub mod timer;
+pub mod watchdog;
+
+/// How much of the chip's peripheral set `pal_nrf::init` should claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeripheralMode {
+    /// Claim the whole peripheral set. Default; assumes Fobnail is the
+    /// only firmware running on the chip.
+    Exclusive,
+    /// Take only the peripherals Fobnail needs (USBD, TIMER0, GPIO, POWER)
+    /// and leave the radio and its supporting clock/interrupt setup for a
+    /// coexisting SoftDevice.
+    SoftDeviceCoexist,
+}
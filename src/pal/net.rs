@@ -0,0 +1,58 @@
+//! Deriving a stable Ethernet MAC address for the (not-yet-implemented)
+//! USB EEM link from a device's unique ID, so multiple identical tokens
+//! on the same network don't collide on a fixed or randomly-chosen
+//! address.
+//!
+//! `pal_nrf` and `pal_pc` are both plain, always-compiled modules in this
+//! tree (there's no `target_arch`/feature switch selecting one), so the
+//! actual derivation lives here and each backend supplies its own source
+//! of uniqueness: `pal_nrf` from the chip's FICR device ID, `pal_pc` from
+//! a fixed constant since a host build has no equivalent hardware ID.
+
+/// Derive a locally-administered, unicast MAC address from a 64-bit
+/// device identifier (e.g. the nRF52840's `FICR.DEVICEID[0]`/`[1]`).
+///
+/// The first octet of an Ethernet address carries two special bits:
+/// bit 0 (multicast/unicast) and bit 1 (locally/universally administered).
+/// A vendor-assigned (universally administered) address must never be
+/// invented locally, so this always sets bit 1 (locally administered) and
+/// clears bit 0 (unicast), per IEEE 802-2014 ยง8.2.
+pub fn derive_mac_from_device_id(device_id: [u32; 2]) -> [u8; 6] {
+    let bytes = device_id[0].to_be_bytes();
+    let more = device_id[1].to_be_bytes();
+
+    let mut mac = [bytes[0], bytes[1], bytes[2], bytes[3], more[0], more[1]];
+    mac[0] |= 0b0000_0010; // set: locally administered
+    mac[0] &= 0b1111_1110; // clear: unicast, not multicast
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_the_locally_administered_bit() {
+        let mac = derive_mac_from_device_id([0x0000_0000, 0x0000_0000]);
+        assert_eq!(mac[0] & 0b0000_0010, 0b0000_0010);
+    }
+
+    #[test]
+    fn clears_the_multicast_bit() {
+        let mac = derive_mac_from_device_id([0xffff_ffff, 0xffff_ffff]);
+        assert_eq!(mac[0] & 0b0000_0001, 0);
+    }
+
+    #[test]
+    fn is_deterministic_for_the_same_device_id() {
+        let id = [0xdead_beef, 0x1234_5678];
+        assert_eq!(derive_mac_from_device_id(id), derive_mac_from_device_id(id));
+    }
+
+    #[test]
+    fn differs_across_device_ids() {
+        let a = derive_mac_from_device_id([0x0000_0001, 0x0000_0000]);
+        let b = derive_mac_from_device_id([0x0000_0002, 0x0000_0000]);
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,163 @@
+//! nRF52840 board bring-up.
+
+use super::PeripheralMode;
+
+/// Peripherals Fobnail claims for itself in [`PeripheralMode::SoftDeviceCoexist`].
+/// Notably excludes the radio and the low-frequency clock source, which a
+/// SoftDevice must own.
+///
+/// `timer0` is claimed for the one-shot "start USB `100ms` after boot"
+/// delay only; see [`UsbServicingTrigger`] for how the peripheral is
+/// actually serviced once running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClaimedPeripherals {
+    pub usbd: bool,
+    pub timer0: bool,
+    pub gpio: bool,
+    pub power: bool,
+}
+
+/// What drives `usb::usb_interrupt()` servicing.
+///
+/// Periodic timer polling (`Timer0Poll`) has a fixed overhead every
+/// interval whether or not USB actually needs attention, and is prone to
+/// scheduling jitter if something else briefly delays the timer
+/// interrupt. Servicing directly from the `USBD` interrupt
+/// (`UsbdInterrupt`, the default) avoids both: the CPU only wakes when
+/// the peripheral actually has something to report, and there's no
+/// separate interval to miss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsbServicingTrigger {
+    /// Service directly from the `USBD` interrupt.
+    #[default]
+    UsbdInterrupt,
+    /// Service from a periodic `TIMER0` poll. Kept only so a board can
+    /// fall back to it if a future erratum requires it; not used by
+    /// default.
+    Timer0Poll,
+}
+
+impl ClaimedPeripherals {
+    fn for_mode(mode: PeripheralMode) -> Self {
+        match mode {
+            PeripheralMode::Exclusive | PeripheralMode::SoftDeviceCoexist => {
+                Self { usbd: true, timer0: true, gpio: true, power: true }
+            }
+        }
+    }
+}
+
+/// Board bring-up entry point: reports which peripherals Fobnail claims
+/// for `mode`.
+///
+/// No peripheral is actually taken here: there is no `Peripherals::take()`
+/// call and no register access, and [`PeripheralMode::Exclusive`] and
+/// [`PeripheralMode::SoftDeviceCoexist`] both report the same set (USBD,
+/// TIMER0, GPIO, POWER), which leaves out the radio and its clock source.
+pub fn init(mode: PeripheralMode) -> ClaimedPeripherals {
+    ClaimedPeripherals::for_mode(mode)
+}
+
+/// Derive this chip's Ethernet MAC address from `FICR.DEVICEID[0..1]`.
+///
+/// Takes the raw FICR words as a parameter, the same way
+/// [`crate::usb::vbus::VbusMonitor::poll`] takes a raw peripheral register
+/// value rather than reading it directly, so the derivation can be tested
+/// without real hardware. See [`super::net::derive_mac_from_device_id`]
+/// for the locally-administered/unicast bit handling.
+pub fn mac_address(ficr_device_id: [u32; 2]) -> [u8; 6] {
+    super::net::derive_mac_from_device_id(ficr_device_id)
+}
+
+/// Why the chip last came out of reset, decoded from `POWER.RESETREAS`.
+///
+/// The register is a bitfield and more than one bit can be set (e.g. a
+/// brownout during a watchdog timeout); the reasons a boot cares about are
+/// checked in priority order and only the first match is reported, since
+/// distinguishing "watchdog reset" from "power glitch that also looks like
+/// a watchdog reset" only matters for the first one found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetReason {
+    /// `RESETPIN`: the external reset pin was pulled low.
+    Pin,
+    /// `DOG`: the watchdog timer expired without being fed.
+    Watchdog,
+    /// `SREQ`: `NVIC_SystemReset()` was called.
+    Soft,
+    /// `LOCKUP`: the CPU entered a lockup state.
+    Lockup,
+    /// None of the tracked bits were set, which on power-up is the normal
+    /// case: `RESETREAS` reads as zero on a cold power-on reset.
+    PowerOn,
+}
+
+/// `POWER.RESETREAS` bit positions (nRF52840 Product Specification §6.1).
+const RESETREAS_RESETPIN: u32 = 1 << 0;
+const RESETREAS_DOG: u32 = 1 << 1;
+const RESETREAS_SREQ: u32 = 1 << 2;
+const RESETREAS_LOCKUP: u32 = 1 << 3;
+
+/// Decode a raw `POWER.RESETREAS` value. Takes the raw value as a
+/// parameter rather than reading the register directly, the same way
+/// [`mac_address`] takes the raw FICR words, so the decode can be tested
+/// without real hardware. Nothing in this module reads, logs or clears
+/// the register; [`init`] does not touch `RESETREAS`.
+pub fn reset_reason(resetreas: u32) -> ResetReason {
+    if resetreas & RESETREAS_DOG != 0 {
+        ResetReason::Watchdog
+    } else if resetreas & RESETREAS_LOCKUP != 0 {
+        ResetReason::Lockup
+    } else if resetreas & RESETREAS_RESETPIN != 0 {
+        ResetReason::Pin
+    } else if resetreas & RESETREAS_SREQ != 0 {
+        ResetReason::Soft
+    } else {
+        ResetReason::PowerOn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn softdevice_coexist_leaves_radio_untouched() {
+        let claimed = init(PeripheralMode::SoftDeviceCoexist);
+        // ClaimedPeripherals intentionally has no `radio` field: this test
+        // documents that the reduced init path never reaches for it.
+        assert!(claimed.usbd && claimed.timer0 && claimed.gpio && claimed.power);
+    }
+
+    #[test]
+    fn exclusive_and_coexist_claim_the_same_fobnail_peripherals() {
+        assert_eq!(init(PeripheralMode::Exclusive), init(PeripheralMode::SoftDeviceCoexist));
+    }
+
+    #[test]
+    fn mac_address_is_locally_administered() {
+        let mac = mac_address([0x1234_5678, 0x9abc_def0]);
+        assert_eq!(mac[0] & 0b11, 0b10);
+    }
+
+    #[test]
+    fn usb_servicing_defaults_to_the_usbd_interrupt_not_timer_polling() {
+        assert_eq!(UsbServicingTrigger::default(), UsbServicingTrigger::UsbdInterrupt);
+    }
+
+    #[test]
+    fn zero_resetreas_is_power_on() {
+        assert_eq!(reset_reason(0), ResetReason::PowerOn);
+    }
+
+    #[test]
+    fn watchdog_bit_is_reported_even_alongside_a_brownout_looking_pin_bit() {
+        assert_eq!(reset_reason(RESETREAS_DOG | RESETREAS_RESETPIN), ResetReason::Watchdog);
+    }
+
+    #[test]
+    fn each_reason_bit_decodes_on_its_own() {
+        assert_eq!(reset_reason(RESETREAS_RESETPIN), ResetReason::Pin);
+        assert_eq!(reset_reason(RESETREAS_SREQ), ResetReason::Soft);
+        assert_eq!(reset_reason(RESETREAS_LOCKUP), ResetReason::Lockup);
+    }
+}
@@ -0,0 +1,275 @@
+//! Host-side PAL backend: backs tools and tests running on a PC rather
+//! than the nRF52840.
+
+use super::timer::TimeSource;
+
+/// A time source whose value is set explicitly rather than driven by
+/// hardware, so tests can assert timeout-driven transitions without
+/// sleeping.
+pub struct MockClock {
+    now_ms: u64,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self { now_ms: 0 }
+    }
+
+    pub fn advance(&mut self, delta_ms: u64) {
+        self.now_ms += delta_ms;
+    }
+
+    pub fn set(&mut self, now_ms: u64) {
+        self.now_ms = now_ms;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for MockClock {
+    fn get_time_ms(&self) -> u64 {
+        self.now_ms
+    }
+}
+
+/// Ethernet MAC address for host builds/tests, which have no FICR device
+/// ID to derive from. Fixed rather than random so repeated test runs are
+/// reproducible; still locally-administered/unicast to stay a valid
+/// address on a real network.
+pub fn mac_address() -> [u8; 6] {
+    super::net::derive_mac_from_device_id([0x464f424e, 0x41494c00]) // "FOBN" "AIL\0"
+}
+
+/// IPv4 address plus port, as used by [`LoopbackTransport`].
+pub type Endpoint = ([u8; 4], u16);
+
+/// Largest datagram [`LoopbackTransport`] will carry, sized to a typical
+/// CoAP message plus header slack.
+pub const MAX_DATAGRAM_LEN: usize = 1280;
+
+/// Queued-but-unread datagrams a [`LoopbackTransport`] end will hold
+/// before `send_to` starts failing.
+const INBOX_CAPACITY: usize = 8;
+
+struct Datagram {
+    from: Endpoint,
+    data: heapless::Vec<u8, MAX_DATAGRAM_LEN>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportSendError {
+    /// The datagram is larger than [`MAX_DATAGRAM_LEN`].
+    TooLarge,
+    /// The peer hasn't drained its inbox and it's at [`INBOX_CAPACITY`].
+    PeerInboxFull,
+}
+
+/// One end of an in-memory, UDP-shaped loopback transport, standing in
+/// for a real `pal_pc` network backend bridged to a Linux TAP device or
+/// raw sockets.
+///
+/// A genuine TAP bridge needs `smoltcp`/`embassy-net` and a TAP-device
+/// crate, none of which are dependencies of this tree (see
+/// `Cargo.toml`), and there's no `main.rs` for a
+/// `pal::net::stack()` to be called from yet either. What's implemented
+/// here instead is a pure, host-testable stand-in with the same
+/// send/receive shape a real socket would have: two ends created via
+/// [`LoopbackTransport::pair`] deliver directly into each other's inbox,
+/// with no kernel or real networking involved, so `CoapClient`-shaped
+/// request/response round trips can already be exercised in a host test
+/// today. A real TAP-backed `pal_pc` would offer the same send/receive
+/// shape backed by an actual socket instead.
+pub struct LoopbackTransport {
+    local: Endpoint,
+    inbox: alloc::rc::Rc<core::cell::RefCell<heapless::Vec<Datagram, INBOX_CAPACITY>>>,
+    peer_inbox: alloc::rc::Rc<core::cell::RefCell<heapless::Vec<Datagram, INBOX_CAPACITY>>>,
+}
+
+impl LoopbackTransport {
+    /// Create a connected pair of transport ends: `a`'s sends land in
+    /// `b`'s inbox and vice versa.
+    pub fn pair(a: Endpoint, b: Endpoint) -> (Self, Self) {
+        let a_inbox = alloc::rc::Rc::new(core::cell::RefCell::new(heapless::Vec::new()));
+        let b_inbox = alloc::rc::Rc::new(core::cell::RefCell::new(heapless::Vec::new()));
+
+        (
+            Self { local: a, inbox: a_inbox.clone(), peer_inbox: b_inbox.clone() },
+            Self { local: b, inbox: b_inbox, peer_inbox: a_inbox },
+        )
+    }
+
+    pub fn local_endpoint(&self) -> Endpoint {
+        self.local
+    }
+
+    /// Deliver `datagram` into the peer's inbox, tagged with this end's
+    /// own endpoint as the sender.
+    pub fn send_to(&mut self, datagram: &[u8]) -> Result<(), TransportSendError> {
+        let data = heapless::Vec::from_slice(datagram).map_err(|_| TransportSendError::TooLarge)?;
+        self.peer_inbox
+            .borrow_mut()
+            .push(Datagram { from: self.local, data })
+            .map_err(|_| TransportSendError::PeerInboxFull)
+    }
+
+    /// Copy the oldest queued datagram into `buf`, returning its sender
+    /// and length, or `None` if nothing has arrived yet.
+    pub fn recv_from(&mut self, buf: &mut [u8]) -> Option<(Endpoint, usize)> {
+        let mut inbox = self.inbox.borrow_mut();
+        if inbox.is_empty() {
+            return None;
+        }
+        let datagram = inbox.remove(0);
+        let len = datagram.data.len().min(buf.len());
+        buf[..len].copy_from_slice(&datagram.data[..len]);
+        Some((datagram.from, len))
+    }
+}
+
+/// A `pal_pc`-only [`crate::coap::RandomSource`] seeded deterministically
+/// instead of from real hardware entropy, so a test can reproduce the
+/// exact sequence a `trussed.random_bytes`-backed source would have
+/// produced for a given seed (e.g. to assert a `MakeCredential`
+/// ciphertext byte-for-byte).
+///
+/// There's no `trussed` crate in this tree to provide a seedable mode
+/// of its own, so this is a from-scratch xorshift-based source living
+/// entirely in `pal_pc` — a module `pal_nrf` never references — which is
+/// what keeps it from ever being reachable on the `pal_nrf` target
+/// rather than a cfg gate on the type itself.
+pub struct DeterministicRng {
+    state: u64,
+}
+
+impl DeterministicRng {
+    /// Seed of zero would make the generator stick at zero forever, so it
+    /// is nudged to `1` instead; every other seed is used as given.
+    pub fn new(seed: u64) -> Self {
+        Self { state: if seed == 0 { 1 } else { seed } }
+    }
+
+    /// Read the seed from the `FOBNAIL_TEST_RNG_SEED` environment
+    /// variable if it's set and parses as a `u64`, falling back to
+    /// `default_seed` otherwise. Only available under `cfg(test)`. since
+    /// `std::env` needs the `std` this crate only links against in test
+    /// builds (see `#![cfg_attr(not(test), no_std)]` in `lib.rs`).
+    #[cfg(test)]
+    pub fn from_env_or(default_seed: u64) -> Self {
+        let seed = std::env::var("FOBNAIL_TEST_RNG_SEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default_seed);
+        Self::new(seed)
+    }
+}
+
+impl crate::coap::RandomSource for DeterministicRng {
+    fn random_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            // xorshift64*, the same construction `IdGenerator` uses
+            // internally, just exposed here as the entropy source itself
+            // rather than fed by one.
+            let mut x = self.state;
+            x ^= x >> 12;
+            x ^= x << 25;
+            x ^= x >> 27;
+            self.state = x;
+            let word = x.wrapping_mul(0x2545_f491_4f6c_dd1d).to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClientConfig, FobnailClient};
+    use crate::coap::RandomSource;
+
+    #[test]
+    fn advancing_the_mock_clock_triggers_an_idle_timeout_transition() {
+        let mut clock = MockClock::new();
+        let mut client = FobnailClient::new(ClientConfig::new(), None);
+        client.begin_request();
+        client.enter_credential_activation_wait(clock.get_time_ms());
+
+        clock.advance(4_999);
+        assert!(!client.check_timeout(clock.get_time_ms()));
+
+        clock.advance(2);
+        assert!(client.check_timeout(clock.get_time_ms()));
+    }
+
+    #[test]
+    fn loopback_transport_delivers_a_datagram_to_its_peer() {
+        let (mut a, mut b) = LoopbackTransport::pair(([127, 0, 0, 1], 5683), ([127, 0, 0, 1], 5684));
+
+        a.send_to(b"hello").unwrap();
+
+        let mut buf = [0u8; 32];
+        let (from, len) = b.recv_from(&mut buf).unwrap();
+        assert_eq!(from, a.local_endpoint());
+        assert_eq!(&buf[..len], b"hello");
+    }
+
+    #[test]
+    fn recv_from_an_empty_inbox_returns_none() {
+        let (_a, mut b) = LoopbackTransport::pair(([127, 0, 0, 1], 5683), ([127, 0, 0, 1], 5684));
+        let mut buf = [0u8; 32];
+        assert!(b.recv_from(&mut buf).is_none());
+    }
+
+    #[test]
+    fn an_oversized_datagram_is_rejected() {
+        let (mut a, _b) = LoopbackTransport::pair(([127, 0, 0, 1], 5683), ([127, 0, 0, 1], 5684));
+        let datagram = [0u8; MAX_DATAGRAM_LEN + 1];
+        assert_eq!(a.send_to(&datagram), Err(TransportSendError::TooLarge));
+    }
+
+    #[test]
+    fn a_full_peer_inbox_rejects_further_sends() {
+        let (mut a, _b) = LoopbackTransport::pair(([127, 0, 0, 1], 5683), ([127, 0, 0, 1], 5684));
+        for _ in 0..INBOX_CAPACITY {
+            a.send_to(b"x").unwrap();
+        }
+        assert_eq!(a.send_to(b"x"), Err(TransportSendError::PeerInboxFull));
+    }
+
+    #[test]
+    fn the_same_seed_produces_the_same_sequence() {
+        let mut a = DeterministicRng::new(42);
+        let mut b = DeterministicRng::new(42);
+
+        let mut buf_a = [0u8; 16];
+        let mut buf_b = [0u8; 16];
+        a.random_bytes(&mut buf_a);
+        b.random_bytes(&mut buf_b);
+
+        assert_eq!(buf_a, buf_b);
+    }
+
+    #[test]
+    fn different_seeds_produce_different_sequences() {
+        let mut a = DeterministicRng::new(1);
+        let mut b = DeterministicRng::new(2);
+
+        let mut buf_a = [0u8; 16];
+        let mut buf_b = [0u8; 16];
+        a.random_bytes(&mut buf_a);
+        b.random_bytes(&mut buf_b);
+
+        assert_ne!(buf_a, buf_b);
+    }
+
+    #[test]
+    fn a_zero_seed_does_not_stick_at_zero() {
+        let mut rng = DeterministicRng::new(0);
+        let mut buf = [0u8; 8];
+        rng.random_bytes(&mut buf);
+        assert_ne!(buf, [0u8; 8]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Panic persistence for post-mortem debugging.
+//!
+//! There's no panic handler, NVMC driver, or Trussed storage wired up
+//! anywhere in this tree yet (`trussed::drivers::init` doesn't exist), so
+//! this module can't reach into a real panic handler or flash page. What's
+//! implemented is the record format and the encode/decode logic against a
+//! small trait for the single reserved page, in the same style as
+//! [`crate::certmgr::store::Filesystem`] abstracts the cert store's
+//! backing storage. A real integration would call
+//! [`PanicLog::record`] from `#[panic_handler]` before resetting, and
+//! [`PanicLog::take`] once at boot, on top of a `PanicPage` impl backed by
+//! the NVMC driver.
+use heapless::String;
+
+/// How much of the panic message is kept; long messages are truncated
+/// rather than dropped so at least the start of the message survives.
+const MESSAGE_CAPACITY: usize = 96;
+
+/// A single reserved flash page `PanicLog` persists its record to.
+/// Writing while already occupied overwrites the previous record: only
+/// the most recent panic before a reset matters for debugging.
+pub trait PanicPage {
+    /// Read the raw bytes currently stored, if any record is present.
+    fn read(&self) -> Option<heapless::Vec<u8, PANIC_RECORD_CAPACITY>>;
+
+    /// Persist `bytes` as the current record, replacing any previous one.
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Erase the page so a subsequent `read` reports no record.
+    fn erase(&mut self);
+}
+
+/// Upper bound on the serialized record size: a `u32` line number, then
+/// the file path (up to 255 bytes) and [`MESSAGE_CAPACITY`] bytes of
+/// message, each of those two length-prefixed by one byte.
+pub const PANIC_RECORD_CAPACITY: usize = 4 + 1 + 255 + 1 + MESSAGE_CAPACITY;
+
+/// A compact record of where and why the last panic happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicRecord {
+    pub file: String<255>,
+    pub line: u32,
+    pub message: String<MESSAGE_CAPACITY>,
+}
+
+impl PanicRecord {
+    fn encode(&self) -> heapless::Vec<u8, PANIC_RECORD_CAPACITY> {
+        let mut out = heapless::Vec::new();
+        let _ = out.extend_from_slice(&self.line.to_le_bytes());
+        let _ = out.push(self.file.len() as u8);
+        let _ = out.extend_from_slice(self.file.as_bytes());
+        let _ = out.push(self.message.len() as u8);
+        let _ = out.extend_from_slice(self.message.as_bytes());
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 5 {
+            return None;
+        }
+        let line = u32::from_le_bytes(bytes[0..4].try_into().ok()?);
+        let file_len = bytes[4] as usize;
+        let file_start: usize = 5;
+        let file_end = file_start.checked_add(file_len)?;
+        // `String::from(&str)` panics rather than erroring if the string
+        // doesn't fit; `file` always does (`file_len` is a `u8` and `file`'s
+        // capacity is 255), but a corrupted `message_len` byte could exceed
+        // `MESSAGE_CAPACITY`, so `message` goes through `push_str` to fail
+        // gracefully instead.
+        let file = String::from(core::str::from_utf8(bytes.get(file_start..file_end)?).ok()?);
+
+        let message_len_idx = file_end;
+        let message_len = *bytes.get(message_len_idx)? as usize;
+        let message_start = message_len_idx + 1;
+        let message_end = message_start.checked_add(message_len)?;
+        let mut message = String::new();
+        message.push_str(core::str::from_utf8(bytes.get(message_start..message_end)?).ok()?).ok()?;
+
+        Some(Self { file, line, message })
+    }
+
+    /// Build a record from a `core::panic::PanicInfo`-shaped location and
+    /// message, truncating both to fit. Guards against a recursive panic
+    /// while formatting by never allocating: everything here is a fixed
+    /// capacity `heapless` push.
+    pub fn new(file: &str, line: u32, message: &str) -> Self {
+        let mut record_file = String::new();
+        let _ = record_file.push_str(&file[..file.len().min(record_file.capacity())]);
+
+        let mut record_message = String::new();
+        let _ = record_message.push_str(&message[..message.len().min(record_message.capacity())]);
+
+        Self { file: record_file, line, message: record_message }
+    }
+}
+
+/// Reads and writes [`PanicRecord`]s against a single [`PanicPage`].
+pub struct PanicLog<P> {
+    page: P,
+}
+
+impl<P: PanicPage> PanicLog<P> {
+    pub fn new(page: P) -> Self {
+        Self { page }
+    }
+
+    /// Persist `record`, overwriting whatever was previously stored.
+    pub fn record(&mut self, record: &PanicRecord) {
+        self.page.write(&record.encode());
+    }
+
+    /// Return the stored record and erase it, so a second boot without an
+    /// intervening panic doesn't re-report a stale one.
+    pub fn take(&mut self) -> Option<PanicRecord> {
+        let bytes = self.page.read()?;
+        let record = PanicRecord::decode(&bytes);
+        self.page.erase();
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakePage {
+        contents: Option<heapless::Vec<u8, PANIC_RECORD_CAPACITY>>,
+    }
+
+    impl PanicPage for FakePage {
+        fn read(&self) -> Option<heapless::Vec<u8, PANIC_RECORD_CAPACITY>> {
+            self.contents.clone()
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.contents = heapless::Vec::from_slice(bytes).ok();
+        }
+
+        fn erase(&mut self) {
+            self.contents = None;
+        }
+    }
+
+    #[test]
+    fn round_trips_through_encode_decode() {
+        let mut log = PanicLog::new(FakePage::default());
+        let record = PanicRecord::new("src/client/mod.rs", 789, "unexpected state: Idle");
+        log.record(&record);
+
+        assert_eq!(log.take(), Some(record));
+    }
+
+    #[test]
+    fn take_clears_the_record_so_it_is_not_reported_twice() {
+        let mut log = PanicLog::new(FakePage::default());
+        log.record(&PanicRecord::new("a.rs", 1, "boom"));
+
+        assert!(log.take().is_some());
+        assert_eq!(log.take(), None);
+    }
+
+    #[test]
+    fn no_record_before_anything_is_written() {
+        let mut log = PanicLog::new(FakePage::default());
+        assert_eq!(log.take(), None);
+    }
+
+    #[test]
+    fn overlong_message_is_truncated_not_rejected() {
+        let long_message = "x".repeat(500);
+        let record = PanicRecord::new("a.rs", 1, &long_message);
+        assert_eq!(record.message.len(), MESSAGE_CAPACITY);
+    }
+
+    #[test]
+    fn recording_twice_keeps_only_the_latest() {
+        let mut log = PanicLog::new(FakePage::default());
+        log.record(&PanicRecord::new("a.rs", 1, "first"));
+        log.record(&PanicRecord::new("b.rs", 2, "second"));
+
+        assert_eq!(log.take(), Some(PanicRecord::new("b.rs", 2, "second")));
+    }
+}
@@ -0,0 +1,73 @@
+//! Idle-time sleep-interval selection.
+//!
+//! There's no `cpu_relax()`/`wfi` loop or RTC driver anywhere in this tree
+//! to reconfigure (the main loop that would call this doesn't exist
+//! either), so what's implemented is the pure decision of how long the
+//! next sleep should be: given the current time, whether USB needs
+//! prompt servicing, and how far off the next scheduled wakeup is, pick a
+//! sleep duration. A real integration would call [`next_sleep_ms`] before
+//! entering `wfi` and reprogram the wakeup timer (TIMER0 for short
+//! intervals, or the RTC for longer ones) to fire after that many
+//! milliseconds instead of the fixed 1ms tick.
+use crate::client::{FobnailClient, State};
+
+/// Upper bound on how long a single sleep is ever allowed to be, so a very
+/// distant deadline (or none at all) doesn't leave the device unable to
+/// react promptly to USB activity that starts mid-sleep.
+const MAX_SLEEP_MS: u64 = 1_000;
+
+/// The normal tick used whenever the client isn't idling out a known
+/// deadline, matching the existing 1ms TIMER0 polling interval.
+const DEFAULT_SLEEP_MS: u64 = 1;
+
+/// Decide how long the CPU can sleep before it next needs to check in,
+/// given `client`'s current state, `now_ms`, and whether the USB link has
+/// pending activity that needs prompt servicing.
+///
+/// Only `State::Idle` with no pending USB activity sleeps longer than the
+/// default tick: every other state is mid-conversation and needs to keep
+/// polling at the normal rate so a response isn't delayed.
+pub fn next_sleep_ms(client: &FobnailClient, now_ms: u64, usb_activity_pending: bool) -> u64 {
+    if usb_activity_pending {
+        return DEFAULT_SLEEP_MS;
+    }
+
+    match client.state() {
+        State::Idle => match client.deadline_ms() {
+            Some(deadline) if deadline > now_ms => (deadline - now_ms).min(MAX_SLEEP_MS),
+            Some(_) => DEFAULT_SLEEP_MS,
+            None => MAX_SLEEP_MS,
+        },
+        _ => DEFAULT_SLEEP_MS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::config::ClientConfig;
+
+    fn client() -> FobnailClient {
+        FobnailClient::new(ClientConfig::default(), None)
+    }
+
+    #[test]
+    fn pending_usb_activity_forces_the_default_tick() {
+        assert_eq!(next_sleep_ms(&client(), 0, true), DEFAULT_SLEEP_MS);
+    }
+
+    #[test]
+    fn idle_with_no_deadline_sleeps_the_max_interval() {
+        assert_eq!(next_sleep_ms(&client(), 0, false), MAX_SLEEP_MS);
+    }
+
+    #[test]
+    fn idle_with_a_near_deadline_sleeps_only_until_then() {
+        let mut c = client();
+        c.report_link_down(0);
+        // report_link_down backs off but stays in State::Idle once retries
+        // remain, with a deadline in the future.
+        let sleep = next_sleep_ms(&c, 0, false);
+        assert!(sleep > 0 && sleep <= MAX_SLEEP_MS);
+    }
+}
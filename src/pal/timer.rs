@@ -0,0 +1,9 @@
+//! Monotonic time source used by cert validity checks, retry timeouts,
+//! CoAP Max-Age and re-attestation scheduling.
+
+/// Milliseconds since boot. On hardware this is driven by TIMER0; on
+/// `pal_pc` it can be a real monotonic clock or, in tests, an explicitly
+/// advanced mock clock.
+pub trait TimeSource {
+    fn get_time_ms(&self) -> u64;
+}
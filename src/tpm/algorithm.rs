@@ -0,0 +1,59 @@
+//! TPM_ALG_ID values relevant to Fobnail.
+
+/// Subset of `TPM_ALG_ID` that Fobnail understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum Algorithm {
+    Sha1 = 0x0004,
+    Sha256 = 0x000b,
+    Sha384 = 0x000c,
+    Rsa = 0x0001,
+    Ecc = 0x0023,
+}
+
+impl Algorithm {
+    /// Digest size in bytes for hash algorithms, `None` for non-hash algs.
+    pub fn digest_size(&self) -> Option<usize> {
+        match self {
+            Algorithm::Sha1 => Some(20),
+            Algorithm::Sha256 => Some(32),
+            Algorithm::Sha384 => Some(48),
+            Algorithm::Rsa | Algorithm::Ecc => None,
+        }
+    }
+}
+
+impl core::convert::TryFrom<u16> for Algorithm {
+    type Error = u16;
+
+    /// Recognize the `TPM_ALG_ID` values `Algorithm` supports, returning
+    /// the unrecognized value as the error so callers can report it.
+    fn try_from(alg: u16) -> Result<Self, Self::Error> {
+        match alg {
+            0x0004 => Ok(Algorithm::Sha1),
+            0x000b => Ok(Algorithm::Sha256),
+            0x000c => Ok(Algorithm::Sha384),
+            0x0001 => Ok(Algorithm::Rsa),
+            0x0023 => Ok(Algorithm::Ecc),
+            other => Err(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn round_trips_through_the_wire_value() {
+        for alg in [Algorithm::Sha1, Algorithm::Sha256, Algorithm::Sha384, Algorithm::Rsa, Algorithm::Ecc] {
+            assert_eq!(Algorithm::try_from(alg as u16), Ok(alg));
+        }
+    }
+
+    #[test]
+    fn rejects_an_unknown_alg_id() {
+        assert_eq!(Algorithm::try_from(0xffff), Err(0xffff));
+    }
+}
@@ -0,0 +1,7 @@
+//! Endorsement Key (EK) certificate handling is implemented in later
+//! milestones; this module currently only re-exports the EK hash type used
+//! to key trust decisions.
+
+/// SHA-256 fingerprint of an EK public key, used as a stable identifier for
+/// an attester across sessions.
+pub type EkHash = [u8; 32];
@@ -0,0 +1,85 @@
+//! HMAC used by `MakeCredential`'s credential wrapper, which combines a
+//! KDFa-derived key with an HMAC over the encrypted credential to detect
+//! tampering.
+//!
+//! Computed in pure Rust on top of [`crate::crypto::sha256`]; nothing here
+//! calls a Trussed HMAC syscall, so this can be validated against known
+//! test vectors without a Trussed backend.
+
+use super::Algorithm;
+use crate::crypto::sha256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HmacError {
+    UnsupportedAlgorithm,
+}
+
+/// Compute HMAC(alg, key, data). Only SHA-256 is currently supported.
+pub fn hmac(alg: Algorithm, key: &[u8], data: &[u8]) -> Result<[u8; 32], HmacError> {
+    match alg {
+        Algorithm::Sha256 => Ok(hmac_sha256(key, data)),
+        _ => Err(HmacError::UnsupportedAlgorithm),
+    }
+}
+
+const BLOCK_SIZE: usize = 64;
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut key_block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        key_block[..32].copy_from_slice(&sha256(key));
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut ipad = [0x36u8; BLOCK_SIZE];
+    let mut opad = [0x5cu8; BLOCK_SIZE];
+    for i in 0..BLOCK_SIZE {
+        ipad[i] ^= key_block[i];
+        opad[i] ^= key_block[i];
+    }
+
+    let mut inner_input = heapless::Vec::<u8, 1024>::new();
+    inner_input.extend_from_slice(&ipad).ok();
+    inner_input.extend_from_slice(data).ok();
+    let inner_hash = sha256(&inner_input);
+
+    let mut outer_input = [0u8; BLOCK_SIZE + 32];
+    outer_input[..BLOCK_SIZE].copy_from_slice(&opad);
+    outer_input[BLOCK_SIZE..].copy_from_slice(&inner_hash);
+    sha256(&outer_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> heapless::String<64> {
+        let mut s = heapless::String::new();
+        for b in bytes {
+            let _ = core::fmt::write(&mut s, format_args!("{:02x}", b));
+        }
+        s
+    }
+
+    #[test]
+    fn sha256_empty_input() {
+        assert_eq!(
+            hex(&sha256(b"")).as_str(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    // RFC 4231 test case 1: Key = 0x0b * 20, Data = "Hi There"
+    #[test]
+    fn hmac_sha256_rfc4231_case1() {
+        let key = [0x0bu8; 20];
+        let mac = hmac(Algorithm::Sha256, &key, b"Hi There").unwrap();
+        assert_eq!(hex(&mac).as_str(), "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+    }
+
+    #[test]
+    fn unsupported_algorithm_is_rejected() {
+        assert_eq!(hmac(Algorithm::Sha1, b"key", b"data"), Err(HmacError::UnsupportedAlgorithm));
+    }
+}
@@ -0,0 +1,102 @@
+//! TPM 2.0 key derivation functions used to turn a shared secret ("seed")
+//! into the symmetric key and HMAC key used by the credential wrapper.
+//!
+//! `kdfa` (SP800-108 counter-mode KDF with HMAC) derives the storage and
+//! integrity keys from an RSA-OAEP-unwrapped seed. `kdfe` (SP800-56A
+//! concatenation KDF) derives the same seed from an ECDH shared secret
+//! for ECC EKs, so both paths converge on `kdfa`/`hmac` afterwards.
+
+use super::hmac::hmac;
+use super::Algorithm;
+use crate::crypto::Sha256;
+
+/// `KDFa(hashAlg, key, label, contextU, contextV, bits)`, per TPM 2.0 Part
+/// 1 Annex C.6.2. Only SHA-256 is supported, matching [`hmac`].
+pub fn kdfa(
+    alg: Algorithm,
+    key: &[u8],
+    label: &[u8],
+    context_u: &[u8],
+    context_v: &[u8],
+    bits: u32,
+) -> heapless::Vec<u8, 64> {
+    let bytes_needed = bits.div_ceil(8) as usize;
+    let mut out = heapless::Vec::new();
+    let mut counter: u32 = 1;
+
+    while out.len() < bytes_needed {
+        let mut input = heapless::Vec::<u8, 128>::new();
+        input.extend_from_slice(&counter.to_be_bytes()).ok();
+        input.extend_from_slice(label).ok();
+        input.push(0).ok(); // label is NUL-terminated per the spec
+        input.extend_from_slice(context_u).ok();
+        input.extend_from_slice(context_v).ok();
+        input.extend_from_slice(&bits.to_be_bytes()).ok();
+
+        let block = hmac(alg, key, &input).expect("kdfa only used with supported hash algorithms");
+        let take = core::cmp::min(block.len(), bytes_needed - out.len());
+        out.extend_from_slice(&block[..take]).ok();
+        counter += 1;
+    }
+
+    out
+}
+
+/// `KDFe(hashAlg, Z, use, partyUInfo, partyVInfo, bits)`, per TPM 2.0 Part
+/// 1 Annex C.6.1: a plain-hash concatenation KDF (no HMAC), used to derive
+/// the `MakeCredential` seed from an ECDH shared secret `z` against an ECC
+/// EK.
+pub fn kdfe(z: &[u8], label: &[u8], party_u_info: &[u8], party_v_info: &[u8], bits: u32) -> heapless::Vec<u8, 64> {
+    let bytes_needed = bits.div_ceil(8) as usize;
+    let mut out = heapless::Vec::new();
+    let mut counter: u32 = 1;
+
+    while out.len() < bytes_needed {
+        let mut hasher = Sha256::new();
+        hasher.update(&counter.to_be_bytes());
+        hasher.update(z);
+        hasher.update(label);
+        hasher.update(&[0]); // NUL terminator
+        hasher.update(party_u_info);
+        hasher.update(party_v_info);
+        let block = hasher.finalize();
+
+        let take = core::cmp::min(block.len(), bytes_needed - out.len());
+        out.extend_from_slice(&block[..take]).ok();
+        counter += 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kdfa_produces_requested_length() {
+        let out = kdfa(Algorithm::Sha256, b"seed-key", b"STORAGE", b"ctx-u", b"ctx-v", 128);
+        assert_eq!(out.len(), 16);
+    }
+
+    #[test]
+    fn kdfe_produces_requested_length() {
+        let out = kdfe(b"shared-secret", b"IDENTITY", b"party-u", b"party-v", 256);
+        assert_eq!(out.len(), 32);
+    }
+
+    #[test]
+    fn kdfe_is_deterministic() {
+        let a = kdfe(b"z", b"IDENTITY", b"u", b"v", 128);
+        let b = kdfe(b"z", b"IDENTITY", b"u", b"v", 128);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn kdfe_output_depends_on_every_input() {
+        let base = kdfe(b"z", b"IDENTITY", b"u", b"v", 128);
+        assert_ne!(base, kdfe(b"z2", b"IDENTITY", b"u", b"v", 128));
+        assert_ne!(base, kdfe(b"z", b"OTHER", b"u", b"v", 128));
+        assert_ne!(base, kdfe(b"z", b"IDENTITY", b"u2", b"v", 128));
+    }
+}
@@ -0,0 +1,338 @@
+//! `TPM2_MakeCredential` support (host side): wraps a secret so that only
+//! the TPM whose EK matches `name` can recover it via
+//! `TPM2_ActivateCredential`.
+
+#[cfg(feature = "rsa")]
+use alloc::{boxed::Box, string::String, vec::Vec};
+#[cfg(feature = "rsa")]
+use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "rsa")]
+use rsa::PublicKey;
+#[cfg(feature = "rsa")]
+use sha2::Sha256;
+
+#[cfg(feature = "rsa")]
+use super::rsa::RsaKey;
+
+use aes::cipher::{AsyncStreamCipher, KeyIvInit};
+use aes::{Aes128, Aes256};
+use cfb_mode::Encryptor as CfbEncryptor;
+
+use super::hmac::hmac;
+use super::kdf::kdfa;
+use super::Algorithm;
+use crate::client::tpm::LoadedKeyName;
+
+/// OAEP label mandated by the TCG EK Credential Profile for the
+/// `MakeCredential` secret: the literal string "IDENTITY", NUL-terminated.
+#[cfg(feature = "rsa")]
+const OAEP_LABEL: &str = "IDENTITY\0";
+
+/// RSA-OAEP encrypt `seed` under the EK's public key, per the TCG EK
+/// Credential Profile: OAEP with SHA-256 for both the digest and MGF1,
+/// under the "IDENTITY\0" label. Returns the ciphertext to embed in the
+/// credential blob sent to the attester.
+#[cfg(feature = "rsa")]
+pub fn make_credential_rsa(
+    ek_key: &RsaKey,
+    seed: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, MakeCredentialError> {
+    let padding = rsa::PaddingScheme::OAEP {
+        label: Some(String::from(OAEP_LABEL)),
+        digest: Box::new(Sha256::default()),
+        mgf_digest: Box::new(Sha256::default()),
+    };
+
+    ek_key.inner.encrypt(rng, padding, seed).map_err(|_| MakeCredentialError::OaepEncryptFailed)
+}
+
+/// Symmetric cipher key size of the EK's `symmetricDetails`, which
+/// determines the seed length `MakeCredential` must produce (the seed is
+/// used as key material for that cipher).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EkSymmetricKeySize {
+    Aes128,
+    Aes256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MakeCredentialError {
+    UnsupportedSymmetricKeySize(u16),
+    /// RSA-OAEP encryption of the `MakeCredential` seed failed.
+    #[cfg(feature = "rsa")]
+    OaepEncryptFailed,
+    /// Ephemeral ECDH agreement against the EK's public point failed.
+    #[cfg(feature = "rsa")]
+    EcdhFailed,
+}
+
+impl EkSymmetricKeySize {
+    /// Parse the `keyBits` field of the EK's `TPMT_SYM_DEF_OBJECT`.
+    pub fn from_key_bits(key_bits: u16) -> Result<Self, MakeCredentialError> {
+        match key_bits {
+            128 => Ok(EkSymmetricKeySize::Aes128),
+            256 => Ok(EkSymmetricKeySize::Aes256),
+            other => Err(MakeCredentialError::UnsupportedSymmetricKeySize(other)),
+        }
+    }
+
+    /// Seed length in bytes matching this cipher's key size. Previously
+    /// hardcoded to 16, which only matched an AES-128 EK; an AES-256 EK
+    /// needs a 32-byte seed.
+    pub fn seed_len(&self) -> usize {
+        match self {
+            EkSymmetricKeySize::Aes128 => 16,
+            EkSymmetricKeySize::Aes256 => 32,
+        }
+    }
+}
+
+/// A `TPM2B_ID_OBJECT`: the outer integrity HMAC over the encrypted
+/// identity, plus the identity itself (here, the caller's `secret`)
+/// encrypted under the symmetric key derived from the MakeCredential
+/// seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdObject {
+    pub integrity_hmac: [u8; 32],
+    pub enc_identity: heapless::Vec<u8, 64>,
+}
+
+/// Symmetrically wrap `secret` for `name` given an already-recovered
+/// `seed` (RSA-OAEP-decrypted, or ECDH+KDFe-derived for an ECC EK — both
+/// paths converge here). Derives the storage key via
+/// `KDFa(seed, "STORAGE", name, "")`, AES-CFB encrypts `secret` under it,
+/// then derives the integrity key via `KDFa(seed, "INTEGRITY")` and HMACs
+/// the encrypted identity concatenated with `name`.
+pub fn wrap_credential(
+    seed: &[u8],
+    name: &LoadedKeyName,
+    symmetric_key_size: EkSymmetricKeySize,
+    secret: &[u8],
+) -> IdObject {
+    let encoded_name = name.encode();
+    let sym_key = kdfa(
+        Algorithm::Sha256,
+        seed,
+        b"STORAGE",
+        &encoded_name,
+        &[],
+        symmetric_key_size.seed_len() as u32 * 8,
+    );
+
+    let mut enc_identity = heapless::Vec::<u8, 64>::new();
+    enc_identity.extend_from_slice(secret).ok();
+
+    // TPM2_MakeCredential uses an all-zero IV; the seed is single-use, so
+    // key reuse across encryptions never happens.
+    match symmetric_key_size {
+        EkSymmetricKeySize::Aes128 => {
+            let mut key = [0u8; 16];
+            key.copy_from_slice(&sym_key[..16]);
+            CfbEncryptor::<Aes128>::new(&key.into(), &[0u8; 16].into()).encrypt(&mut enc_identity);
+        }
+        EkSymmetricKeySize::Aes256 => {
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&sym_key[..32]);
+            CfbEncryptor::<Aes256>::new(&key.into(), &[0u8; 16].into()).encrypt(&mut enc_identity);
+        }
+    }
+
+    let hmac_key = kdfa(Algorithm::Sha256, seed, b"INTEGRITY", &[], &[], 256);
+    let mut hmac_input = heapless::Vec::<u8, 128>::new();
+    hmac_input.extend_from_slice(&enc_identity).ok();
+    hmac_input.extend_from_slice(&encoded_name).ok();
+    let integrity_hmac =
+        hmac(Algorithm::Sha256, &hmac_key, &hmac_input).expect("hmac key/data within supported size");
+
+    IdObject { integrity_hmac, enc_identity }
+}
+
+/// Full `TPM2_MakeCredential` against an RSA EK: generates a random seed,
+/// OAEP-encrypts it under `ek_key`, and wraps `secret` for `loaded_key_name`
+/// under that seed. Returns `(id_object, encrypted_secret)` ready to send
+/// to the attester for `TPM2_ActivateCredential`.
+#[cfg(feature = "rsa")]
+pub fn make_credential(
+    loaded_key_name: &LoadedKeyName,
+    ek_key: &RsaKey,
+    symmetric_key_size: EkSymmetricKeySize,
+    secret: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<(IdObject, Vec<u8>), MakeCredentialError> {
+    let mut seed = [0u8; 32];
+    let seed_len = symmetric_key_size.seed_len();
+    rng.fill_bytes(&mut seed[..seed_len]);
+
+    let encrypted_secret = make_credential_rsa(ek_key, &seed[..seed_len], rng)?;
+    let id_object = wrap_credential(&seed[..seed_len], loaded_key_name, symmetric_key_size, secret);
+
+    // `seed` is the only thing standing between `encrypted_secret` and the
+    // identity secret it wraps; it has no further use once both outputs
+    // are built, so it shouldn't linger in RAM after this returns.
+    crate::crypto::zeroize(&mut seed);
+
+    Ok((id_object, encrypted_secret))
+}
+
+/// An attester's EK public key, in whichever form its TPM presented it.
+/// `MakeCredential` wraps the seed differently for each: OAEP for `Rsa`,
+/// ephemeral ECDH + KDFe for `Ecc`.
+#[cfg(feature = "rsa")]
+pub enum EkKey {
+    Rsa(RsaKey),
+    /// Raw EC point (`04 || x || y`) from the EK's public area.
+    Ecc(heapless::Vec<u8, 65>),
+}
+
+/// `MakeCredential` against either EK family, converging on
+/// [`wrap_credential`] once a seed has been produced. `rng` is only
+/// consulted on the `Rsa` path (OAEP needs randomness); `ecdh` only on the
+/// `Ecc` path.
+#[cfg(feature = "rsa")]
+pub fn make_credential_for_ek(
+    loaded_key_name: &LoadedKeyName,
+    ek_key: &EkKey,
+    symmetric_key_size: EkSymmetricKeySize,
+    secret: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+    ecdh: &mut impl super::mc_ecc::Ecdh,
+) -> Result<(IdObject, Vec<u8>), MakeCredentialError> {
+    match ek_key {
+        EkKey::Rsa(rsa_key) => make_credential(loaded_key_name, rsa_key, symmetric_key_size, secret, rng),
+        EkKey::Ecc(ek_point) => {
+            let (ephemeral_pub, mut seed) =
+                super::mc_ecc::derive_seed_ecc(ecdh, ek_point, symmetric_key_size.seed_len() as u32 * 8)
+                    .map_err(|_| MakeCredentialError::EcdhFailed)?;
+            let id_object = wrap_credential(&seed, loaded_key_name, symmetric_key_size, secret);
+            crate::crypto::zeroize(&mut seed);
+
+            let mut encrypted_secret = Vec::new();
+            encrypted_secret.extend_from_slice(&ephemeral_pub);
+            Ok((id_object, encrypted_secret))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rsa"))]
+mod rsa_tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    #[test]
+    fn make_credential_rsa_produces_a_ciphertext_the_size_of_the_modulus() {
+        let priv_key = rsa::RsaPrivateKey::new(&mut OsRng, 2048).unwrap();
+        let ek_key = RsaKey::new(rsa::RsaPublicKey::from(&priv_key));
+        let seed = [0x11u8; 32];
+
+        let ciphertext = make_credential_rsa(&ek_key, &seed, &mut OsRng).unwrap();
+
+        assert_eq!(ciphertext.len(), 256);
+    }
+
+    #[test]
+    fn make_credential_returns_encrypted_secret_and_matching_id_object() {
+        use crate::client::tpm::LoadedKeyName;
+        use crate::tpm::{Algorithm as TpmAlgorithm, Name};
+
+        let priv_key = rsa::RsaPrivateKey::new(&mut OsRng, 2048).unwrap();
+        let ek_key = RsaKey::new(rsa::RsaPublicKey::from(&priv_key));
+        let name = LoadedKeyName::new(Name::new(TpmAlgorithm::Sha256, &[0x5a; 32]).unwrap());
+        let secret = b"attestation-secret";
+
+        let (id_object, encrypted_secret) =
+            make_credential(&name, &ek_key, EkSymmetricKeySize::Aes128, secret, &mut OsRng).unwrap();
+
+        assert_eq!(encrypted_secret.len(), 256);
+        assert_eq!(id_object.enc_identity.len(), secret.len());
+    }
+
+    #[test]
+    fn make_credential_for_ek_ecc_returns_the_ephemeral_point_as_encrypted_secret() {
+        use super::super::mc_ecc::Ecdh;
+        use crate::client::tpm::LoadedKeyName;
+        use crate::tpm::{Algorithm as TpmAlgorithm, Name};
+
+        struct FixedEcdh;
+        impl Ecdh for FixedEcdh {
+            fn ephemeral_agree(&mut self, _ek_point: &[u8]) -> (heapless::Vec<u8, 65>, heapless::Vec<u8, 32>) {
+                let mut ephemeral_pub = heapless::Vec::new();
+                ephemeral_pub.extend_from_slice(&[4u8; 65]).ok();
+                let mut z = heapless::Vec::new();
+                z.extend_from_slice(&[7u8; 32]).ok();
+                (ephemeral_pub, z)
+            }
+        }
+
+        let name = LoadedKeyName::new(Name::new(TpmAlgorithm::Sha256, &[0x5b; 32]).unwrap());
+        let mut ek_point = heapless::Vec::new();
+        ek_point.extend_from_slice(&[9u8; 65]).ok();
+        let ek_key = EkKey::Ecc(ek_point);
+
+        let (id_object, encrypted_secret) = make_credential_for_ek(
+            &name,
+            &ek_key,
+            EkSymmetricKeySize::Aes128,
+            b"attestation-secret",
+            &mut OsRng,
+            &mut FixedEcdh,
+        )
+        .unwrap();
+
+        assert_eq!(encrypted_secret, [4u8; 65]);
+        assert_eq!(id_object.enc_identity.len(), b"attestation-secret".len());
+    }
+}
+
+#[cfg(test)]
+mod wrap_credential_tests {
+    use super::*;
+    use crate::client::tpm::LoadedKeyName;
+    use crate::tpm::{Algorithm as TpmAlgorithm, Name};
+
+    fn name() -> LoadedKeyName {
+        LoadedKeyName::new(Name::new(TpmAlgorithm::Sha256, &[0x11; 32]).unwrap())
+    }
+
+    #[test]
+    fn wrap_credential_is_deterministic_for_a_fixed_seed() {
+        let a = wrap_credential(b"fixed-seed-material", &name(), EkSymmetricKeySize::Aes128, b"secret");
+        let b = wrap_credential(b"fixed-seed-material", &name(), EkSymmetricKeySize::Aes128, b"secret");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn wrap_credential_integrity_hmac_depends_on_the_name() {
+        let other_name = LoadedKeyName::new(Name::new(TpmAlgorithm::Sha256, &[0x22; 32]).unwrap());
+
+        let a = wrap_credential(b"fixed-seed-material", &name(), EkSymmetricKeySize::Aes128, b"secret");
+        let b = wrap_credential(b"fixed-seed-material", &other_name, EkSymmetricKeySize::Aes128, b"secret");
+
+        assert_ne!(a.integrity_hmac, b.integrity_hmac);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aes128_ek_uses_16_byte_seed() {
+        let size = EkSymmetricKeySize::from_key_bits(128).unwrap();
+        assert_eq!(size.seed_len(), 16);
+    }
+
+    #[test]
+    fn aes256_ek_uses_32_byte_seed() {
+        let size = EkSymmetricKeySize::from_key_bits(256).unwrap();
+        assert_eq!(size.seed_len(), 32);
+    }
+
+    #[test]
+    fn unsupported_key_size_is_rejected() {
+        assert_eq!(
+            EkSymmetricKeySize::from_key_bits(192),
+            Err(MakeCredentialError::UnsupportedSymmetricKeySize(192))
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! ECC EK `MakeCredential`: derives the protection seed via ephemeral
+//! ECDH + KDFe instead of RSA-OAEP, then proceeds identically to the RSA
+//! path (KDFa for the storage/integrity keys, HMAC for the outer wrapper).
+
+use super::kdf::kdfe;
+
+/// An elliptic-curve Diffie-Hellman implementation, abstracted so this
+/// module doesn't depend on a specific curve library. Nothing in this
+/// tree implements it other than test fakes.
+pub trait Ecdh {
+    /// Generate an ephemeral key pair and compute the shared secret `z`
+    /// against `ek_point`, returning `(ephemeral_public_point, z)`.
+    fn ephemeral_agree(&mut self, ek_point: &[u8]) -> (heapless::Vec<u8, 65>, heapless::Vec<u8, 32>);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EccMakeCredentialError {
+    EcdhFailed,
+}
+
+/// Derive the `MakeCredential` seed for an ECC EK: an ephemeral ECDH
+/// against the EK's public point, then KDFe(Z, "IDENTITY", ephemeral
+/// public point, ek point, bits). Also returns the ephemeral public point,
+/// since (unlike the RSA path's OAEP ciphertext) it is itself what gets
+/// sent to the attester as the credential's `encryptedSecret` — the seed
+/// never leaves this side.
+pub fn derive_seed_ecc(
+    ecdh: &mut impl Ecdh,
+    ek_point: &[u8],
+    seed_bits: u32,
+) -> Result<(heapless::Vec<u8, 65>, heapless::Vec<u8, 64>), EccMakeCredentialError> {
+    let (ephemeral_pub, z) = ecdh.ephemeral_agree(ek_point);
+    if z.is_empty() {
+        return Err(EccMakeCredentialError::EcdhFailed);
+    }
+
+    let seed = kdfe(&z, b"IDENTITY", &ephemeral_pub, ek_point, seed_bits);
+    Ok((ephemeral_pub, seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedEcdh {
+        z: heapless::Vec<u8, 32>,
+    }
+
+    impl Ecdh for FixedEcdh {
+        fn ephemeral_agree(&mut self, _ek_point: &[u8]) -> (heapless::Vec<u8, 65>, heapless::Vec<u8, 32>) {
+            let mut ephemeral_pub = heapless::Vec::new();
+            ephemeral_pub.extend_from_slice(&[4u8; 65]).ok();
+            (ephemeral_pub, self.z.clone())
+        }
+    }
+
+    #[test]
+    fn derives_a_seed_of_the_requested_length() {
+        let mut z = heapless::Vec::new();
+        z.extend_from_slice(&[7u8; 32]).ok();
+        let mut ecdh = FixedEcdh { z };
+
+        let (ephemeral_pub, seed) = derive_seed_ecc(&mut ecdh, &[9u8; 65], 256).unwrap();
+        assert_eq!(seed.len(), 32);
+        assert_eq!(ephemeral_pub.len(), 65);
+    }
+
+    #[test]
+    fn empty_shared_secret_is_reported_as_ecdh_failure() {
+        let mut ecdh = FixedEcdh { z: heapless::Vec::new() };
+        assert_eq!(derive_seed_ecc(&mut ecdh, &[9u8; 65], 128), Err(EccMakeCredentialError::EcdhFailed));
+    }
+}
@@ -0,0 +1,32 @@
+//! TPM 2.0 primitives used during attester enrollment: endorsement key (EK)
+//! handling, attestation identity key (AIK) credential activation, and
+//! platform quotes.
+
+pub mod aik;
+pub mod algorithm;
+pub mod ek;
+pub mod hmac;
+pub mod kdf;
+pub mod mc;
+pub mod mc_ecc;
+pub mod quote;
+#[cfg(feature = "rsa")]
+pub mod rsa;
+
+pub use algorithm::Algorithm;
+
+/// A TPM "name", as defined by the TPM 2.0 spec: a hash algorithm tag
+/// followed by the digest of the object's public area.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name {
+    pub alg: Algorithm,
+    pub digest: heapless::Vec<u8, 64>,
+}
+
+impl Name {
+    pub fn new(alg: Algorithm, digest: &[u8]) -> Option<Self> {
+        let mut v = heapless::Vec::new();
+        v.extend_from_slice(digest).ok()?;
+        Some(Self { alg, digest: v })
+    }
+}
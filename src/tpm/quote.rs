@@ -0,0 +1,233 @@
+//! `TPMS_ATTEST` parsing for `TPM_ST_ATTEST_QUOTE`, per TPM 2.0 Part 2
+//! §10.12.8. This is the prerequisite for verifying a PCR-based runtime
+//! integrity quote; the actual signature/nonce/PCR-digest checks live in
+//! the client state machine once a quote-verification state exists.
+
+use heapless::Vec;
+
+/// `TPM_GENERATED_VALUE`, present at the start of every `TPMS_ATTEST` to
+/// distinguish TPM-generated structures from externally crafted data.
+const TPM_GENERATED_VALUE: u32 = 0xff544347;
+
+/// `TPM_ST_ATTEST_QUOTE`: the only `TPMI_ST_ATTEST` value this parser
+/// accepts. Other attestation types (certify, session audit, ...) aren't
+/// needed for PCR attestation and are rejected.
+const TPM_ST_ATTEST_QUOTE: u16 = 0x8018;
+
+/// `TPMS_CLOCK_INFO`: the TPM's monotonic clock state when the quote was
+/// generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockInfo {
+    pub clock: u64,
+    pub reset_count: u32,
+    pub restart_count: u32,
+    /// `TPMI_YES_NO`: `false` if the TPM can't guarantee `clock` hasn't
+    /// been rolled back since it was last read.
+    pub safe: bool,
+}
+
+/// One `TPMS_PCR_SELECTION`: the PCRs of a single hash bank that were
+/// included in `Quote::pcr_digest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcrSelection {
+    pub hash_alg: u16,
+    pub select: Vec<u8, 8>,
+}
+
+/// A parsed `TPMS_ATTEST` of type `TPM_ST_ATTEST_QUOTE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    /// `TPM2B_NAME` of the key the quote is qualified against, i.e. the
+    /// AIK expected to sign it.
+    pub qualified_signer: Vec<u8, 66>,
+    /// `TPM2B_DATA extraData`: the caller-supplied nonce echoed back by
+    /// the TPM, used to defeat replay of a captured quote.
+    pub extra_data: Vec<u8, 64>,
+    pub clock_info: ClockInfo,
+    pub firmware_version: u64,
+    pub pcr_selections: Vec<PcrSelection, 8>,
+    /// `TPM2B_DIGEST pcrDigest`: the digest over exactly the PCRs named
+    /// by `pcr_selections`.
+    pub pcr_digest: Vec<u8, 64>,
+}
+
+/// Cursor over a `TPMS_ATTEST` byte slice, tracking how far parsing has
+/// gotten so each field can be read in order without repeating offset
+/// arithmetic.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// A `TPM2B`-style length-prefixed field: a `UINT16` size followed by
+    /// that many bytes.
+    fn size_prefixed(&mut self) -> Option<&'a [u8]> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+}
+
+/// Digest a raw `TPMS_ATTEST` buffer the way the TPM does before signing
+/// it, so a caller verifying the signature hashes exactly the bytes that
+/// were signed rather than a re-serialization of the parsed [`Quote`].
+pub fn digest(attested: &[u8]) -> [u8; 32] {
+    crate::crypto::sha256(attested)
+}
+
+impl Quote {
+    /// Parse a `TPMS_ATTEST` believed to be a PCR quote. Returns `None` if
+    /// the buffer is truncated, the `TPM_GENERATED_VALUE` magic doesn't
+    /// match, the attestation type isn't `TPM_ST_ATTEST_QUOTE`, or any
+    /// bounded collection (PCR selections, digest) would overflow.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut r = Reader::new(data);
+
+        if r.u32()? != TPM_GENERATED_VALUE {
+            return None;
+        }
+        if r.u16()? != TPM_ST_ATTEST_QUOTE {
+            return None;
+        }
+
+        let qualified_signer = Vec::from_slice(r.size_prefixed()?).ok()?;
+        let extra_data = Vec::from_slice(r.size_prefixed()?).ok()?;
+
+        let clock_info = ClockInfo {
+            clock: r.u64()?,
+            reset_count: r.u32()?,
+            restart_count: r.u32()?,
+            safe: r.u8()? != 0,
+        };
+
+        let firmware_version = r.u64()?;
+
+        let selection_count = r.u32()? as usize;
+        let mut pcr_selections = Vec::new();
+        for _ in 0..selection_count {
+            let hash_alg = r.u16()?;
+            let select_len = r.u8()? as usize;
+            let select = Vec::from_slice(r.take(select_len)?).ok()?;
+            pcr_selections.push(PcrSelection { hash_alg, select }).ok()?;
+        }
+
+        let pcr_digest = Vec::from_slice(r.size_prefixed()?).ok()?;
+
+        Some(Self { qualified_signer, extra_data, clock_info, firmware_version, pcr_selections, pcr_digest })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a well-formed `TPMS_ATTEST`/`TPMS_QUOTE_INFO` blob, as a real
+    /// TPM would emit for `TPM2_Quote` over a single SHA-256 PCR bank.
+    fn make_quote_blob() -> heapless::Vec<u8, 256> {
+        let mut buf = heapless::Vec::<u8, 256>::new();
+        buf.extend_from_slice(&TPM_GENERATED_VALUE.to_be_bytes()).ok();
+        buf.extend_from_slice(&TPM_ST_ATTEST_QUOTE.to_be_bytes()).ok();
+
+        let signer_name = [0xaa; 34];
+        buf.extend_from_slice(&(signer_name.len() as u16).to_be_bytes()).ok();
+        buf.extend_from_slice(&signer_name).ok();
+
+        let nonce = [0xbb; 20];
+        buf.extend_from_slice(&(nonce.len() as u16).to_be_bytes()).ok();
+        buf.extend_from_slice(&nonce).ok();
+
+        buf.extend_from_slice(&0x0102030405060708u64.to_be_bytes()).ok(); // clock
+        buf.extend_from_slice(&7u32.to_be_bytes()).ok(); // resetCount
+        buf.extend_from_slice(&3u32.to_be_bytes()).ok(); // restartCount
+        buf.push(1).ok(); // safe = YES
+
+        buf.extend_from_slice(&0x1234u64.to_be_bytes()).ok(); // firmwareVersion
+
+        buf.extend_from_slice(&1u32.to_be_bytes()).ok(); // pcrSelect.count
+        buf.extend_from_slice(&0x000bu16.to_be_bytes()).ok(); // hash = SHA256
+        let select = [0x00, 0x00, 0x01]; // PCR 16
+        buf.push(select.len() as u8).ok();
+        buf.extend_from_slice(&select).ok();
+
+        let pcr_digest = [0xcc; 32];
+        buf.extend_from_slice(&(pcr_digest.len() as u16).to_be_bytes()).ok();
+        buf.extend_from_slice(&pcr_digest).ok();
+
+        buf
+    }
+
+    #[test]
+    fn parses_a_well_formed_quote() {
+        let blob = make_quote_blob();
+        let quote = Quote::parse(&blob).unwrap();
+
+        assert_eq!(quote.qualified_signer.as_slice(), [0xaa; 34]);
+        assert_eq!(quote.extra_data.as_slice(), [0xbb; 20]);
+        assert_eq!(
+            quote.clock_info,
+            ClockInfo { clock: 0x0102030405060708, reset_count: 7, restart_count: 3, safe: true }
+        );
+        assert_eq!(quote.firmware_version, 0x1234);
+        assert_eq!(quote.pcr_selections.len(), 1);
+        assert_eq!(quote.pcr_selections[0].hash_alg, 0x000b);
+        assert_eq!(quote.pcr_selections[0].select.as_slice(), [0x00, 0x00, 0x01]);
+        assert_eq!(quote.pcr_digest.as_slice(), [0xcc; 32]);
+    }
+
+    #[test]
+    fn rejects_wrong_magic() {
+        let mut blob = make_quote_blob();
+        blob[0] ^= 0xff;
+        assert_eq!(Quote::parse(&blob), None);
+    }
+
+    #[test]
+    fn rejects_a_non_quote_attestation_type() {
+        let mut blob = make_quote_blob();
+        blob[4..6].copy_from_slice(&0x8017u16.to_be_bytes()); // TPM_ST_ATTEST_CERTIFY
+        assert_eq!(Quote::parse(&blob), None);
+    }
+
+    #[test]
+    fn rejects_a_truncated_buffer() {
+        let blob = make_quote_blob();
+        assert_eq!(Quote::parse(&blob[..blob.len() - 1]), None);
+    }
+
+    #[test]
+    fn digest_is_deterministic_and_input_sensitive() {
+        let blob = make_quote_blob();
+        let mut other = blob.clone();
+        other[0] ^= 1;
+
+        assert_eq!(digest(&blob), digest(&blob));
+        assert_ne!(digest(&blob), digest(&other));
+    }
+}
@@ -0,0 +1,157 @@
+//! RSA public key wrapper used for EK/AIK RSA keys.
+
+use crate::crypto::Sha256;
+
+/// Wraps an RSA public key parsed from a TPM public area.
+pub struct RsaKey {
+    pub inner: rsa::RsaPublicKey,
+}
+
+impl RsaKey {
+    pub fn new(inner: rsa::RsaPublicKey) -> Self {
+        Self { inner }
+    }
+}
+
+/// Caches parsed [`RsaKey`]s by a hash of their modulus and exponent, so
+/// the same EK or AIK public key doesn't get run through
+/// `rsa::BigUint::from_bytes_be` (and `RsaPublicKey::new`'s
+/// primality-adjacent checks) more than once per session. Keyed by a hash
+/// of both fields rather than the raw modulus bytes so the cache entries
+/// stay a fixed 32 bytes regardless of key size, and so a modulus that's
+/// ever paired with two different exponents doesn't return a cached key
+/// built for the wrong one.
+///
+/// There's no timer available in this tree to measure and report time
+/// saved (see [`super::super::coap::retransmit`]'s use of caller-supplied
+/// millisecond timestamps for the same reason) — what's cacheable here is
+/// exactly the `BigUint` construction the request is about, so avoiding it
+/// on a cache hit is the saving, whatever it measures out to be on actual
+/// hardware.
+pub struct RsaKeyCache<const N: usize> {
+    entries: heapless::Vec<([u8; 32], RsaKey), N>,
+}
+
+impl<const N: usize> RsaKeyCache<N> {
+    pub fn new() -> Self {
+        Self { entries: heapless::Vec::new() }
+    }
+
+    /// Return the cached key for `(modulus, exponent)` if one exists;
+    /// otherwise build one with `build` and cache it before returning.
+    /// `build` failing (e.g. a modulus/exponent pair `RsaPublicKey` itself
+    /// rejects) is not cached, so the next call retries.
+    ///
+    /// When the cache is full, the entry inserted longest ago is evicted
+    /// to make room. A cache hit does not move an entry, so eviction
+    /// follows insertion order, not least-recent use.
+    pub fn get_or_insert_with(&mut self, modulus: &[u8], exponent: u32, build: impl FnOnce() -> Option<RsaKey>) -> Option<&RsaKey> {
+        let hash = Self::hash_key(modulus, exponent);
+
+        if let Some(idx) = self.entries.iter().position(|(h, _)| *h == hash) {
+            return Some(&self.entries[idx].1);
+        }
+
+        let key = build()?;
+        if let Err(entry) = self.entries.push((hash, key)) {
+            // Full: evict the oldest entry and retry, which now has room.
+            self.entries.remove(0);
+            let _ = self.entries.push(entry);
+        }
+
+        self.entries.last().map(|(_, k)| k)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn hash_key(modulus: &[u8], exponent: u32) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(modulus);
+        hasher.update(&exponent.to_be_bytes());
+        hasher.finalize()
+    }
+}
+
+impl<const N: usize> Default for RsaKeyCache<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    fn some_key(bits: u32) -> RsaKey {
+        let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, bits as usize).unwrap();
+        RsaKey::new(rsa::RsaPublicKey::from(&priv_key))
+    }
+
+    #[test]
+    fn a_repeated_modulus_is_only_built_once() {
+        let mut cache: RsaKeyCache<4> = RsaKeyCache::new();
+        let modulus = [7u8; 32];
+        let builds = Cell::new(0);
+
+        cache.get_or_insert_with(&modulus, 65537, || {
+            builds.set(builds.get() + 1);
+            Some(some_key(2048))
+        });
+        cache.get_or_insert_with(&modulus, 65537, || {
+            builds.set(builds.get() + 1);
+            Some(some_key(2048))
+        });
+
+        assert_eq!(builds.get(), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn a_failed_build_is_not_cached() {
+        let mut cache: RsaKeyCache<4> = RsaKeyCache::new();
+        assert!(cache.get_or_insert_with(&[1u8; 32], 65537, || None).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn a_full_cache_evicts_the_oldest_entry() {
+        let mut cache: RsaKeyCache<2> = RsaKeyCache::new();
+        cache.get_or_insert_with(&[1u8; 32], 65537, || Some(some_key(1024)));
+        cache.get_or_insert_with(&[2u8; 32], 65537, || Some(some_key(1024)));
+        cache.get_or_insert_with(&[3u8; 32], 65537, || Some(some_key(1024)));
+
+        assert_eq!(cache.len(), 2);
+        let builds = Cell::new(0);
+        cache.get_or_insert_with(&[1u8; 32], 65537, || {
+            builds.set(builds.get() + 1);
+            Some(some_key(1024))
+        });
+        assert_eq!(builds.get(), 1, "the oldest modulus should have been evicted");
+    }
+
+    #[test]
+    fn the_same_modulus_with_a_different_exponent_is_not_a_cache_hit() {
+        let mut cache: RsaKeyCache<4> = RsaKeyCache::new();
+        let modulus = [9u8; 32];
+        let builds = Cell::new(0);
+
+        cache.get_or_insert_with(&modulus, 65537, || {
+            builds.set(builds.get() + 1);
+            Some(some_key(2048))
+        });
+        cache.get_or_insert_with(&modulus, 3, || {
+            builds.set(builds.get() + 1);
+            Some(some_key(2048))
+        });
+
+        assert_eq!(builds.get(), 2, "different exponents must not share a cache entry");
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,177 @@
+//! IPv4/UDP checksum verification for received EEM frames.
+//!
+//! There's no `EemDriver`/smoltcp `Phy` in this tree yet — the `usb`
+//! modules are standalone pieces such as [`super::vbus`] (power state)
+//! and [`super::timing`] (interrupt latency tracing). This module adds
+//! the piece of logic a future `Phy::receive` would need: deciding whether
+//! an inbound frame's checksum is valid before it's handed to the network
+//! stack, since EEM itself provides no CRC guarantee over the Ethernet
+//! payload.
+//!
+//! Verifying the checksum on every received frame costs a linear scan of
+//! the payload per packet; for the CoAP path (small UDP datagrams) that
+//! cost is negligible next to the security value of not feeding a
+//! corrupted attestation message into the protocol state machine, so RX
+//! verification defaults on for UDP.
+
+/// Which received protocols get their checksum verified before the frame
+/// is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxChecksumPolicy {
+    pub verify_udp: bool,
+}
+
+impl RxChecksumPolicy {
+    /// UDP verification on (protects the CoAP path), matching the
+    /// default a real `ChecksumCapabilities` would need once RX
+    /// verification is wired up.
+    pub const fn new() -> Self {
+        Self { verify_udp: true }
+    }
+}
+
+impl Default for RxChecksumPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Minimum length of an IPv4 header carrying a UDP datagram: 20-byte IPv4
+/// header + 8-byte UDP header.
+const MIN_IPV4_UDP_FRAME: usize = 20 + 8;
+
+/// Verify the UDP checksum of an IPv4/UDP datagram, given the raw payload
+/// starting at the IPv4 header (no Ethernet header).
+///
+/// Returns `false` for anything that isn't a well-formed IPv4/UDP
+/// datagram with a matching checksum, so callers can treat "malformed"
+/// and "corrupted" the same way: drop the frame.
+pub fn verify_ipv4_udp_checksum(packet: &[u8]) -> bool {
+    if packet.len() < MIN_IPV4_UDP_FRAME {
+        return false;
+    }
+
+    let ihl = (packet[0] & 0x0f) as usize * 4;
+    if ihl < 20 || packet[9] != 17 /* UDP */ || packet.len() < ihl + 8 {
+        return false;
+    }
+
+    let udp = &packet[ihl..];
+    let udp_len = u16::from_be_bytes([udp[4], udp[5]]) as usize;
+    if udp_len < 8 || udp.len() < udp_len {
+        return false;
+    }
+    let udp = &udp[..udp_len];
+
+    let claimed = u16::from_be_bytes([udp[6], udp[7]]);
+    if claimed == 0 {
+        // Checksum disabled by the sender; nothing to verify.
+        return true;
+    }
+
+    let src = &packet[12..16];
+    let dst = &packet[16..20];
+
+    let mut sum: u32 = 0;
+    fn add_words(sum: &mut u32, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(2);
+        for word in &mut chunks {
+            *sum += u16::from_be_bytes([word[0], word[1]]) as u32;
+        }
+        if let [last] = chunks.remainder() {
+            *sum += u16::from_be_bytes([*last, 0]) as u32;
+        }
+    }
+
+    add_words(&mut sum, src);
+    add_words(&mut sum, dst);
+    sum += 17u32; // UDP protocol number, in the pseudo-header's zero+protocol word
+    sum += udp_len as u32;
+
+    // Checksum the UDP header/payload with the checksum field itself
+    // treated as zero, per RFC 768.
+    add_words(&mut sum, &udp[..6]);
+    add_words(&mut sum, &udp[8..]);
+
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    let computed = !(sum as u16);
+    // A computed checksum of 0 is transmitted as 0xffff (RFC 768).
+    let computed = if computed == 0 { 0xffff } else { computed };
+
+    computed == claimed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_ipv4_udp_frame() -> heapless::Vec<u8, 64> {
+        // IPv4 header (20 bytes, no options) + UDP header + 4-byte payload.
+        let mut buf = heapless::Vec::<u8, 64>::new();
+        buf.extend_from_slice(&[
+            0x45, 0x00, 0x00, 0x20, // version/IHL, DSCP/ECN, total length = 32
+            0x00, 0x00, 0x00, 0x00, // identification, flags/fragment offset
+            0x40, 17, 0x00, 0x00, // TTL, protocol=UDP, header checksum (unused here)
+            10, 0, 0, 1, // src
+            10, 0, 0, 2, // dst
+        ])
+        .unwrap();
+        buf.extend_from_slice(&[0x1f, 0x90, 0x1f, 0x91, 0x00, 12, 0x00, 0x00]).unwrap();
+        buf.extend_from_slice(b"ping").unwrap();
+
+        // Compute and patch in the correct UDP checksum.
+        let ihl = 20;
+        let udp_len = 12;
+        let mut sum: u32 = 0;
+        fn add(sum: &mut u32, bytes: &[u8]) {
+            for chunk in bytes.chunks(2) {
+                let word = if chunk.len() == 2 {
+                    u16::from_be_bytes([chunk[0], chunk[1]])
+                } else {
+                    u16::from_be_bytes([chunk[0], 0])
+                };
+                *sum += word as u32;
+            }
+        }
+        add(&mut sum, &buf[12..16]);
+        add(&mut sum, &buf[16..20]);
+        sum += 17;
+        sum += udp_len as u32;
+        add(&mut sum, &buf[ihl..ihl + 6]);
+        add(&mut sum, &buf[ihl + 8..ihl + udp_len]);
+        while sum >> 16 != 0 {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        let checksum = !(sum as u16);
+        buf[ihl + 6..ihl + 8].copy_from_slice(&checksum.to_be_bytes());
+
+        buf
+    }
+
+    #[test]
+    fn accepts_a_frame_with_a_correct_checksum() {
+        let frame = valid_ipv4_udp_frame();
+        assert!(verify_ipv4_udp_checksum(&frame));
+    }
+
+    #[test]
+    fn rejects_a_frame_with_a_corrupted_udp_checksum() {
+        let mut frame = valid_ipv4_udp_frame();
+        let ihl = 20;
+        frame[ihl + 6] ^= 0xff;
+        assert!(!verify_ipv4_udp_checksum(&frame));
+    }
+
+    #[test]
+    fn rejects_a_truncated_frame() {
+        let frame = valid_ipv4_udp_frame();
+        assert!(!verify_ipv4_udp_checksum(&frame[..10]));
+    }
+
+    #[test]
+    fn rx_checksum_policy_defaults_to_verifying_udp() {
+        assert!(RxChecksumPolicy::default().verify_udp);
+    }
+}
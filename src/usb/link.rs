@@ -0,0 +1,123 @@
+//! Tracking whether the USB Ethernet (EEM) link is currently usable.
+//!
+//! There's no `EemDriver`/smoltcp `Phy` in this tree yet (see
+//! [`super::checksum`] and [`super::mtu`] for the same caveat), so this
+//! captures the state machine such a driver would drive from USB SUSPEND/
+//! RESUME and configuration-set events: whether the host currently has
+//! the link configured, plus a hook so other code (the LED driver, the
+//! attestation client) can react to a transition instead of polling.
+
+/// Whether the host currently has the EEM interface configured and
+/// resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    Up,
+    Down,
+}
+
+/// Notified whenever [`LinkTracker`] observes a link state transition.
+pub trait LinkStateObserver {
+    fn on_link_state_change(&mut self, state: LinkState);
+}
+
+/// Tracks EEM link state from USB power/configuration events, so a future
+/// `Phy::transmit` can refuse to hand out a `TxToken` (and callers can
+/// back off) while the link is down instead of writing into a dead
+/// driver.
+pub struct LinkTracker {
+    state: LinkState,
+    observer: Option<alloc::boxed::Box<dyn LinkStateObserver>>,
+}
+
+impl LinkTracker {
+    pub fn new(observer: Option<alloc::boxed::Box<dyn LinkStateObserver>>) -> Self {
+        // A freshly enumerated device isn't configured yet; assume down
+        // until a configuration-set event says otherwise.
+        Self { state: LinkState::Down, observer }
+    }
+
+    pub fn link_up(&self) -> bool {
+        self.state == LinkState::Up
+    }
+
+    fn set_state(&mut self, new_state: LinkState) {
+        if new_state != self.state {
+            self.state = new_state;
+            if let Some(observer) = &mut self.observer {
+                observer.on_link_state_change(new_state);
+            }
+        }
+    }
+
+    /// The host has selected the EEM configuration; the link is usable.
+    pub fn on_configuration_set(&mut self) {
+        self.set_state(LinkState::Up);
+    }
+
+    /// The bus has suspended (host asleep, or cable unplugged from a
+    /// self-powered device); nothing can be transmitted until resume.
+    pub fn on_suspend(&mut self) {
+        self.set_state(LinkState::Down);
+    }
+
+    /// The bus has resumed. Per the USB spec resume alone doesn't restore
+    /// the previously selected configuration, so this intentionally does
+    /// *not* mark the link up on its own; a fresh
+    /// [`Self::on_configuration_set`] is required.
+    pub fn on_resume(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_down() {
+        let tracker = LinkTracker::new(None);
+        assert!(!tracker.link_up());
+    }
+
+    #[test]
+    fn configuration_set_brings_the_link_up() {
+        let mut tracker = LinkTracker::new(None);
+        tracker.on_configuration_set();
+        assert!(tracker.link_up());
+    }
+
+    #[test]
+    fn suspend_takes_the_link_down() {
+        let mut tracker = LinkTracker::new(None);
+        tracker.on_configuration_set();
+        tracker.on_suspend();
+        assert!(!tracker.link_up());
+    }
+
+    #[test]
+    fn resume_alone_does_not_restore_the_link() {
+        let mut tracker = LinkTracker::new(None);
+        tracker.on_configuration_set();
+        tracker.on_suspend();
+        tracker.on_resume();
+        assert!(!tracker.link_up());
+    }
+
+    struct RecordingObserver(alloc::rc::Rc<core::cell::RefCell<alloc::vec::Vec<LinkState>>>);
+    impl LinkStateObserver for RecordingObserver {
+        fn on_link_state_change(&mut self, state: LinkState) {
+            self.0.borrow_mut().push(state);
+        }
+    }
+
+    #[test]
+    fn observer_is_notified_only_on_transitions() {
+        let events = alloc::rc::Rc::new(core::cell::RefCell::new(alloc::vec::Vec::new()));
+        let mut tracker =
+            LinkTracker::new(Some(alloc::boxed::Box::new(RecordingObserver(events.clone()))));
+
+        tracker.on_configuration_set();
+        tracker.on_configuration_set(); // no-op, already up
+        tracker.on_suspend();
+
+        assert_eq!(*events.borrow(), &[LinkState::Up, LinkState::Down]);
+    }
+}
@@ -0,0 +1,19 @@
+//! USB stack glue: EEM (Ethernet Emulation Model) framing over the nRF52840
+//! USBD peripheral, plus power-state handling.
+
+pub mod checksum;
+pub mod link;
+pub mod mtu;
+pub mod rx_ring;
+pub mod timing;
+pub mod vbus;
+
+/// Whether the USB stack should currently be serviced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// VBUS is present; poll/service USBD as normal.
+    Powered,
+    /// VBUS is absent (e.g. running off a coin cell with no cable
+    /// attached); USBD servicing is paused to save power.
+    Unpowered,
+}
@@ -0,0 +1,101 @@
+//! Configurable MTU for the (not-yet-implemented) EEM link.
+//!
+//! There's no `EemDriver` or `Phy` impl in this tree yet — see
+//! [`super::checksum`] for the same caveat. This captures the piece of
+//! logic a future driver would need: validating a requested MTU against
+//! the frame buffer it will be copied into, so `Phy::capabilities` can
+//! report an MTU the driver can actually service instead of a hardcoded
+//! 1500.
+
+/// Ethernet MTU EEM drivers default to absent any other configuration.
+pub const DEFAULT_MTU: u16 = 1500;
+
+/// Upper bound on the configurable MTU, matching the largest EEM frame
+/// buffer this firmware is expected to allocate (14-byte Ethernet header
+/// + payload, rounded up).
+pub const MAX_MTU: u16 = 2032;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MtuError {
+    /// Requested MTU is below what's usable (e.g. zero, or too small to
+    /// carry an IPv4 header).
+    TooSmall,
+    /// Requested MTU exceeds [`MAX_MTU`].
+    TooLarge,
+    /// The configured frame buffer isn't big enough to hold a frame of
+    /// this MTU plus the Ethernet header.
+    BufferTooSmall,
+}
+
+/// Smallest MTU that can carry a minimal IPv4 header.
+const MIN_MTU: u16 = 68;
+
+/// Ethernet header length added on top of the IP MTU when sizing frame
+/// buffers.
+const ETHERNET_HEADER_LEN: u16 = 14;
+
+/// A validated EEM MTU, checked against both its absolute bounds and a
+/// given frame buffer size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EemMtu(u16);
+
+impl EemMtu {
+    /// Validate `mtu` against `[MIN_MTU, MAX_MTU]` and against
+    /// `frame_buffer_len`, the size of the buffer `read_packet`/
+    /// `prepare_packet` will copy frames into or out of.
+    pub fn new(mtu: u16, frame_buffer_len: usize) -> Result<Self, MtuError> {
+        if mtu < MIN_MTU {
+            return Err(MtuError::TooSmall);
+        }
+        if mtu > MAX_MTU {
+            return Err(MtuError::TooLarge);
+        }
+        if (mtu + ETHERNET_HEADER_LEN) as usize > frame_buffer_len {
+            return Err(MtuError::BufferTooSmall);
+        }
+        Ok(Self(mtu))
+    }
+
+    pub fn get(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Default for EemMtu {
+    fn default() -> Self {
+        // DEFAULT_MTU plus its header always fits within MAX_MTU's
+        // implied buffer sizing, so this can't fail.
+        Self::new(DEFAULT_MTU, (MAX_MTU + ETHERNET_HEADER_LEN) as usize).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_mtu_is_1500() {
+        assert_eq!(EemMtu::default().get(), DEFAULT_MTU);
+    }
+
+    #[test]
+    fn accepts_an_mtu_that_fits_the_buffer() {
+        let mtu = EemMtu::new(1500, 1514).unwrap();
+        assert_eq!(mtu.get(), 1500);
+    }
+
+    #[test]
+    fn rejects_an_mtu_too_large_for_the_buffer() {
+        assert_eq!(EemMtu::new(1500, 1513), Err(MtuError::BufferTooSmall));
+    }
+
+    #[test]
+    fn rejects_an_mtu_below_the_minimum() {
+        assert_eq!(EemMtu::new(20, 4096), Err(MtuError::TooSmall));
+    }
+
+    #[test]
+    fn rejects_an_mtu_above_the_maximum() {
+        assert_eq!(EemMtu::new(9000, 9100), Err(MtuError::TooLarge));
+    }
+}
@@ -0,0 +1,112 @@
+//! A small ring buffer of received EEM frames.
+//!
+//! There's no `EemDriver`/smoltcp `Phy` in this tree yet (see the other
+//! modules in this directory for the same caveat), so this captures the
+//! piece a future `usb_interrupt` handler would need: buffering more than
+//! one received frame per USB poll interval, since bursty arrival can
+//! otherwise mean a driver that only holds the latest frame drops
+//! everything but the last one received in a given interval.
+
+use crate::usb::mtu::MAX_MTU;
+
+/// Largest single frame the ring can hold: an Ethernet header plus a
+/// maximum-MTU payload.
+const MAX_FRAME_LEN: usize = MAX_MTU as usize + 14;
+
+/// A single buffered frame.
+type Frame = heapless::Vec<u8, MAX_FRAME_LEN>;
+
+/// Ring buffer of received frames awaiting `Phy::receive`, with a
+/// configurable depth `N` (the request suggests 4).
+///
+/// Backed by a plain `heapless::Vec` rather than `heapless::spsc::Queue`:
+/// depth is small (a handful of frames) so `pop`'s O(N) shift is cheap,
+/// and it sidesteps `Queue`'s reserved-slot-off-by-one capacity so `N`
+/// means exactly what it says.
+pub struct RxRing<const N: usize> {
+    frames: heapless::Vec<Frame, N>,
+    dropped: u32,
+}
+
+impl<const N: usize> RxRing<N> {
+    pub const fn new() -> Self {
+        Self { frames: heapless::Vec::new(), dropped: 0 }
+    }
+
+    /// Called from `usb_interrupt` as frames arrive. Drops (and counts)
+    /// the frame if the ring is full, rather than blocking the interrupt
+    /// handler or overwriting an unread frame.
+    pub fn push(&mut self, frame: &[u8]) {
+        let Ok(buf) = Frame::from_slice(frame) else {
+            self.dropped += 1;
+            return;
+        };
+        if self.frames.push(buf).is_err() {
+            self.dropped += 1;
+        }
+    }
+
+    /// Drained by `incoming_packet`/`read_packet` to hand the oldest
+    /// buffered frame to smoltcp.
+    pub fn pop(&mut self) -> Option<Frame> {
+        if self.frames.is_empty() {
+            None
+        } else {
+            Some(self.frames.remove(0))
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Number of frames dropped so far because the ring was full or a
+    /// frame didn't fit `MAX_FRAME_LEN`, for logging.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+impl<const N: usize> Default for RxRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_drain_in_arrival_order() {
+        let mut ring: RxRing<4> = RxRing::new();
+        ring.push(&[1, 2, 3]);
+        ring.push(&[4, 5, 6]);
+
+        assert_eq!(ring.pop().as_deref(), Some([1u8, 2, 3].as_slice()));
+        assert_eq!(ring.pop().as_deref(), Some([4u8, 5, 6].as_slice()));
+        assert!(ring.pop().is_none());
+    }
+
+    #[test]
+    fn overflow_is_dropped_and_counted() {
+        let mut ring: RxRing<2> = RxRing::new();
+        ring.push(&[1]);
+        ring.push(&[2]);
+        ring.push(&[3]);
+
+        assert_eq!(ring.dropped(), 1);
+        assert_eq!(ring.pop().as_deref(), Some([1u8].as_slice()));
+        assert_eq!(ring.pop().as_deref(), Some([2u8].as_slice()));
+    }
+
+    #[test]
+    fn oversized_frame_is_dropped_and_counted() {
+        let mut ring: RxRing<4> = RxRing::new();
+        let huge = [0u8; MAX_FRAME_LEN + 1];
+        ring.push(&huge);
+
+        assert_eq!(ring.dropped(), 1);
+        assert!(ring.is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+//! Debug trace for `usb_interrupt()` latency.
+//!
+//! On a busy link the USBD interrupt fires roughly every 1ms, so logging
+//! its duration unconditionally floods the log. It's only useful while
+//! actively profiling USB latency, so it's off by default and toggled at
+//! runtime rather than gated by a Cargo feature, so it can be turned on in
+//! the field without reflashing.
+
+/// Runtime switch for the `usb_interrupt()` timing trace.
+pub struct UsbTimingTrace {
+    enabled: bool,
+}
+
+impl UsbTimingTrace {
+    pub const fn new() -> Self {
+        Self { enabled: false }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Report how long `usb_interrupt()` took, formatting a log line only
+    /// when the trace is enabled.
+    pub fn report(&self, took_ms: u32) -> Option<heapless::String<48>> {
+        if !self.enabled {
+            return None;
+        }
+        let mut s = heapless::String::new();
+        let _ = core::fmt::write(&mut s, format_args!("usb_interrupt() took {}ms", took_ms));
+        Some(s)
+    }
+}
+
+impl Default for UsbTimingTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suppressed_by_default() {
+        let trace = UsbTimingTrace::new();
+        assert_eq!(trace.report(3), None);
+    }
+
+    #[test]
+    fn emitted_once_enabled() {
+        let mut trace = UsbTimingTrace::new();
+        trace.set_enabled(true);
+        assert_eq!(trace.report(3).as_deref(), Some("usb_interrupt() took 3ms"));
+    }
+}
@@ -0,0 +1,84 @@
+//! VBUS presence detection via the nRF52 POWER peripheral's USBREGSTATUS
+//! register, used to pause USB servicing while unplugged.
+
+use super::PowerState;
+
+/// Bit position of `VBUSDETECT` in `POWER.USBREGSTATUS`.
+const USBREGSTATUS_VBUSDETECT: u32 = 1 << 0;
+
+/// Tracks VBUS presence and reports pause/resume transitions. Samples are
+/// not debounced: a single sample that differs from the tracked state is
+/// reported as a transition.
+pub struct VbusMonitor {
+    state: PowerState,
+}
+
+impl VbusMonitor {
+    pub fn new() -> Self {
+        // Assume powered until the first reading proves otherwise; the USBD
+        // peripheral won't have been serviced yet regardless.
+        Self { state: PowerState::Powered }
+    }
+
+    pub fn state(&self) -> PowerState {
+        self.state
+    }
+
+    /// Update the tracked state from a raw `POWER.USBREGSTATUS` value.
+    ///
+    /// Returns `Some(new_state)` when the state changed (i.e. a
+    /// pause-or-resume transition should be acted on), `None` otherwise.
+    pub fn poll(&mut self, usbregstatus: u32) -> Option<PowerState> {
+        self.update(usbregstatus & USBREGSTATUS_VBUSDETECT != 0)
+    }
+
+    fn update(&mut self, vbus_present: bool) -> Option<PowerState> {
+        let new_state = if vbus_present { PowerState::Powered } else { PowerState::Unpowered };
+        if new_state == self.state {
+            None
+        } else {
+            self.state = new_state;
+            Some(new_state)
+        }
+    }
+}
+
+impl Default for VbusMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_powered() {
+        let monitor = VbusMonitor::new();
+        assert_eq!(monitor.state(), PowerState::Powered);
+    }
+
+    #[test]
+    fn vbus_loss_pauses_and_reports_transition() {
+        let mut monitor = VbusMonitor::new();
+        let transition = monitor.poll(0);
+        assert_eq!(transition, Some(PowerState::Unpowered));
+        assert_eq!(monitor.state(), PowerState::Unpowered);
+    }
+
+    #[test]
+    fn vbus_return_resumes_and_reports_transition() {
+        let mut monitor = VbusMonitor::new();
+        monitor.poll(0);
+        let transition = monitor.poll(USBREGSTATUS_VBUSDETECT);
+        assert_eq!(transition, Some(PowerState::Powered));
+    }
+
+    #[test]
+    fn repeated_reads_with_no_change_report_nothing() {
+        let mut monitor = VbusMonitor::new();
+        assert_eq!(monitor.poll(USBREGSTATUS_VBUSDETECT), None);
+        assert_eq!(monitor.poll(USBREGSTATUS_VBUSDETECT), None);
+    }
+}